@@ -0,0 +1,38 @@
+package com.github.dedinc.jsonfixer4j;
+
+/**
+ * Thrown when the native library cannot produce a trustworthy result.
+ * Position information is -1 (offset) and 0 (line, column) when the problem has no location.
+ */
+public class JSONFixException extends RuntimeException {
+
+    private final long offset;
+    private final int line;
+    private final int column;
+
+    public JSONFixException(String message, long offset, int line, int column) {
+        super(message);
+        this.offset = offset;
+        this.line = line;
+        this.column = column;
+    }
+
+    /** Byte offset in the UTF-8 encoded input, or -1 */
+    public long getOffset() {
+        return offset;
+    }
+
+    /** 1-based line, or 0 */
+    public int getLine() {
+        return line;
+    }
+
+    /** 1-based column in characters, or 0 */
+    public int getColumn() {
+        return column;
+    }
+
+    public boolean hasPosition() {
+        return offset >= 0;
+    }
+}
@@ -0,0 +1,183 @@
+package com.github.dedinc.jsonfixer4j;
+
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+import java.util.function.LongConsumer;
+
+/**
+ * Configuration for {@link JSONFixerRust#create(JSONFixerOptions)}.
+ * Every builder method is mirrored by a native function that applies it to the Rust corrector.
+ */
+public final class JSONFixerOptions {
+
+    /** What the corrector does with a kind of damage; the order matches the native side */
+    public enum Action {
+        /** Apply the built-in repair */
+        REPAIR,
+        /** Remove the offending token or entry */
+        DROP,
+        /** Refuse the input; {@code tryAutocorrect} throws {@link JSONFixException} */
+        ERROR
+    }
+
+    /** How {@code NaN} and {@code Infinity} are written; the order matches the native side */
+    public enum NonFiniteNumbers {
+        NULL,
+        STRING,
+        ERROR
+    }
+
+    /** How a key repeated within one object is resolved; the order matches the native side */
+    public enum DuplicateKeys {
+        KEEP_FIRST,
+        KEEP_LAST,
+        /** Collect every value into an array */
+        COLLECT,
+        /** Merge objects recursively; for any other value the last one wins */
+        MERGE,
+        /** Store repeats under {@code key_2}, {@code key_3} and so on */
+        RENAME,
+        ERROR
+    }
+
+    /** Where {@code key: value} pairs in an array end up; the order matches the native side */
+    public enum ArrayPairs {
+        /** Turn an array of nothing but pairs into an object, otherwise wrap each pair */
+        OBJECT,
+        /** Wrap each pair in an object of its own */
+        WRAP,
+        ERROR
+    }
+
+    private final List<LongConsumer> steps;
+
+    private JSONFixerOptions(List<LongConsumer> steps) {
+        this.steps = Collections.unmodifiableList(new ArrayList<>(steps));
+    }
+
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    /** Permissive defaults: repair everything */
+    public static JSONFixerOptions defaults() {
+        return builder().build();
+    }
+
+    void applyTo(long handle) {
+        for (LongConsumer step : steps) {
+            step.accept(handle);
+        }
+    }
+
+    public static final class Builder {
+
+        private final List<LongConsumer> steps = new ArrayList<>();
+
+        private Builder() {
+        }
+
+        private Builder step(LongConsumer step) {
+            steps.add(step);
+            return this;
+        }
+
+        /** Action for every kind of repair without its own action */
+        public Builder defaultAction(Action action) {
+            return step(h -> JSONFixerRust.nativeDefaultAction(h, action.ordinal()));
+        }
+
+        /** Refuse any input that needs a repair, unless overridden per kind afterwards */
+        public Builder strict() {
+            return defaultAction(Action.ERROR);
+        }
+
+        /** Partial or miscased literals such as {@code tr} or {@code NULL} */
+        public Builder partialLiterals(Action action) {
+            return step(h -> JSONFixerRust.nativePartialLiterals(h, action.ordinal()));
+        }
+
+        /** Bare identifiers such as {@code {key: value}} */
+        public Builder identifiers(Action action) {
+            return step(h -> JSONFixerRust.nativeIdentifiers(h, action.ordinal()));
+        }
+
+        /** Object keys without a colon and value */
+        public Builder missingColon(Action action) {
+            return step(h -> JSONFixerRust.nativeMissingColon(h, action.ordinal()));
+        }
+
+        /** Keys without a value such as {@code {"a":,}} and empty array slots such as {@code [1,,2]} */
+        public Builder missingValues(Action action) {
+            return step(h -> JSONFixerRust.nativeMissingValues(h, action.ordinal()));
+        }
+
+        /** Closing brackets without a matching opener */
+        public Builder unmatchedClosers(Action action) {
+            return step(h -> JSONFixerRust.nativeUnmatchedClosers(h, action.ordinal()));
+        }
+
+        /** Input from which no value could be recovered */
+        public Builder emptyFallback(Action action) {
+            return step(h -> JSONFixerRust.nativeEmptyFallback(h, action.ordinal()));
+        }
+
+        public Builder nonFiniteNumbers(NonFiniteNumbers policy) {
+            return step(h -> JSONFixerRust.nativeNonFiniteNumbers(h, policy.ordinal()));
+        }
+
+        /** Keys repeated within one object; {@code KEEP_LAST} unless set */
+        public Builder duplicateKeys(DuplicateKeys policy) {
+            return step(h -> JSONFixerRust.nativeDuplicateKeys(h, policy.ordinal()));
+        }
+
+        /** {@code key: value} pairs inside arrays; {@code OBJECT} unless set */
+        public Builder arrayPairs(ArrayPairs policy) {
+            return step(h -> JSONFixerRust.nativeArrayPairs(h, policy.ordinal()));
+        }
+
+        /** Sort object keys alphabetically instead of keeping input order */
+        public Builder sortKeys(boolean enabled) {
+            return step(h -> JSONFixerRust.nativeSortKeys(h, enabled));
+        }
+
+        /** Repair only the JSON embedded in prose or Markdown fences */
+        public Builder extractEmbedded(boolean enabled) {
+            return step(h -> JSONFixerRust.nativeExtractEmbedded(h, enabled));
+        }
+
+        /** Indent the output */
+        public Builder pretty(boolean enabled) {
+            return step(h -> JSONFixerRust.nativePretty(h, enabled));
+        }
+
+        /** Maximum nesting depth, at most 512; deeper containers become {@code null} */
+        public Builder maxDepth(long limit) {
+            return step(h -> JSONFixerRust.nativeMaxDepth(h, limit));
+        }
+
+        /** Maximum input size in UTF-8 bytes */
+        public Builder maxInputSize(long limit) {
+            return step(h -> JSONFixerRust.nativeMaxInputSize(h, limit));
+        }
+
+        public Builder maxTokens(long limit) {
+            return step(h -> JSONFixerRust.nativeMaxTokens(h, limit));
+        }
+
+        /** Maximum length of a string in UTF-8 bytes */
+        public Builder maxStringLength(long limit) {
+            return step(h -> JSONFixerRust.nativeMaxStringLength(h, limit));
+        }
+
+        /** Maximum number of repairs {@code tryAutocorrect} applies before giving up */
+        public Builder maxRepairs(long limit) {
+            return step(h -> JSONFixerRust.nativeMaxRepairs(h, limit));
+        }
+
+        public JSONFixerOptions build() {
+            return new JSONFixerOptions(steps);
+        }
+    }
+}
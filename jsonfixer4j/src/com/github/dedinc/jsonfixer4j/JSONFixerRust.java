@@ -1,6 +1,6 @@
 package com.github.dedinc.jsonfixer4j;
 
-public final class JSONFixerRust {
+public final class JSONFixerRust implements AutoCloseable {
 
     static {
 
@@ -12,9 +12,132 @@ public final class JSONFixerRust {
         }
     }
 
-    public native String autocorrect(String json);
+    /** Native corrector, or 0 for the calling thread's shared default corrector */
+    private long handle;
+    private final boolean owned;
+
+    /**
+     * Uses the default options. Safe to share between threads without locking,
+     * and needs no {@link #close()}.
+     */
+    public JSONFixerRust() {
+        this.handle = 0;
+        this.owned = false;
+    }
+
+    private JSONFixerRust(long handle) {
+        this.handle = handle;
+        this.owned = true;
+    }
+
+    /**
+     * Allocates a native corrector with the given options. Calls on one instance are
+     * serialized; close it to free the native memory.
+     */
+    public static JSONFixerRust create(JSONFixerOptions options) {
+        long handle = nativeCreate();
+        try {
+            options.applyTo(handle);
+        } catch (RuntimeException e) {
+            nativeFree(handle);
+            throw e;
+        }
+        return new JSONFixerRust(handle);
+    }
+
+    /**
+     * Repairs the input, falling back to {@code {}} when nothing can be recovered.
+     *
+     * @throws IllegalArgumentException if {@code json} is null
+     * @throws IllegalStateException if this corrector is closed
+     */
+    public String autocorrect(String json) {
+        if (!owned) {
+            return nativeAutocorrect(0, json);
+        }
+        synchronized (this) {
+            return nativeAutocorrect(openHandle(), json);
+        }
+    }
+
+    /**
+     * Repairs the input, or explains why no trustworthy result exists.
+     *
+     * @throws JSONFixException if the input is empty, holds no JSON, exceeds a limit
+     *         or needs a repair the options do not allow
+     * @throws IllegalArgumentException if {@code json} is null
+     * @throws IllegalStateException if this corrector is closed
+     */
+    public String tryAutocorrect(String json) {
+        if (!owned) {
+            return nativeTryAutocorrect(0, json);
+        }
+        synchronized (this) {
+            return nativeTryAutocorrect(openHandle(), json);
+        }
+    }
+
+    /** Frees the native corrector; later calls throw {@link IllegalStateException} */
+    @Override
+    public synchronized void close() {
+        if (owned && handle != 0) {
+            nativeFree(handle);
+            handle = 0;
+        }
+    }
+
+    private long openHandle() {
+        if (handle == 0) {
+            throw new IllegalStateException("corrector is closed");
+        }
+        return handle;
+    }
 
     public static String fix(String json) {
         return new JSONFixerRust().autocorrect(json);
     }
-}
\ No newline at end of file
+
+    private static native long nativeCreate();
+
+    private static native void nativeFree(long handle);
+
+    private static native String nativeAutocorrect(long handle, String json);
+
+    private static native String nativeTryAutocorrect(long handle, String json);
+
+    static native void nativeDefaultAction(long handle, int action);
+
+    static native void nativePartialLiterals(long handle, int action);
+
+    static native void nativeIdentifiers(long handle, int action);
+
+    static native void nativeMissingColon(long handle, int action);
+
+    static native void nativeMissingValues(long handle, int action);
+
+    static native void nativeUnmatchedClosers(long handle, int action);
+
+    static native void nativeEmptyFallback(long handle, int action);
+
+    static native void nativeNonFiniteNumbers(long handle, int policy);
+
+    static native void nativeDuplicateKeys(long handle, int policy);
+
+    static native void nativeArrayPairs(long handle, int policy);
+
+    static native void nativeSortKeys(long handle, boolean enabled);
+
+    static native void nativeExtractEmbedded(long handle, boolean enabled);
+
+    static native void nativePretty(long handle, boolean enabled);
+
+    static native void nativeMaxDepth(long handle, long limit);
+
+    static native void nativeMaxInputSize(long handle, long limit);
+
+    static native void nativeMaxTokens(long handle, long limit);
+
+    static native void nativeMaxStringLength(long handle, long limit);
+
+    static native void nativeMaxRepairs(long handle, long limit);
+}
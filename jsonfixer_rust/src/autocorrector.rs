@@ -1,8 +1,20 @@
+#[cfg(feature = "serde")]
+use crate::de;
+use crate::error::FixError;
+use crate::extract::{self, Extraction};
+use crate::lines;
+use crate::options::{CorrectorOptions, RepairAction};
 use crate::parser::JSONParser;
+use crate::report::{Repair, RepairKind, Repaired, RepairedValues, Violation};
 use crate::serializer::JSONSerializer;
+use crate::token::{Span, Token, TokenKind};
 use crate::token_fixer::JSONTokenFixer;
 use crate::tokenizer::JSONTokenizer;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
 use serde_json::{Map, Value};
+#[cfg(feature = "serde")]
+use serde_path_to_error::Segment;
 
 /// Main JSON auto-correction engine
 /// Combines tokenization, fixing, parsing, and serialization
@@ -10,51 +22,477 @@ pub struct JSONAutoCorrector {
     tokenizer: JSONTokenizer,
     parser: JSONParser,
     serializer: JSONSerializer,
+    options: CorrectorOptions,
 }
 
 impl JSONAutoCorrector {
     pub fn new() -> Self {
+        Self::with_options(CorrectorOptions::default())
+    }
+
+    /// Create a corrector with a custom repair policy
+    pub fn with_options(options: CorrectorOptions) -> Self {
         Self {
-            tokenizer: JSONTokenizer::new(),
-            parser: JSONParser::new(),
+            tokenizer: JSONTokenizer::with_options(options.clone()),
+            parser: JSONParser::with_options(options.clone()),
             serializer: JSONSerializer::new(),
+            options,
         }
     }
 
+    /// Repair policy used by this corrector
+    pub fn options(&self) -> &CorrectorOptions {
+        &self.options
+    }
+
     /// Auto-correct broken JSON string
     /// This is the main entry point for JSON correction
     pub fn autocorrect(&mut self, input: &str) -> String {
-        // Step 1: Tokenize the input
-        let tokens = self.tokenizer.tokenize(input);
+        self.autocorrect_with_report(input).json
+    }
 
-        // Step 2: Fix token stream (add missing brackets, etc.)
-        let fixed_tokens = JSONTokenFixer::fix_tokens(tokens);
+    /// Auto-correct and return pretty-printed JSON
+    pub fn autocorrect_pretty(&mut self, input: &str) -> String {
+        let mut repairs = Vec::new();
+        let result = self.correct(input.as_bytes(), &mut repairs);
+        self.serializer.serialize_pretty(&result)
+    }
 
-        // Step 3: Parse tokens into JSON value
-        let parse_result = self.parser.parse(&fixed_tokens, 0);
+    /// Auto-correct broken JSON string, or explain why no trustworthy result exists
+    ///
+    /// Fails on empty input, input from which nothing could be recovered, exceeded limits
+    /// and repairs whose action is `RepairAction::Error`. The infallible methods apply
+    /// the built-in repair in all of those cases instead.
+    pub fn try_autocorrect(&mut self, input: &str) -> Result<Repaired, FixError> {
+        self.try_autocorrect_bytes(input.as_bytes())
+    }
 
-        // Step 4: Get the result value or default to empty object
-        let result = match parse_result.value {
-            Some(value) => value,
-            None => Value::Object(Map::new()),
+    /// Like `try_autocorrect`, for raw bytes that should contain UTF-8 JSON
+    /// Invalid UTF-8 is replaced and reported as `RepairKind::InvalidUtf8`
+    pub fn try_autocorrect_bytes(&mut self, input: &[u8]) -> Result<Repaired, FixError> {
+        self.check_input(input)?;
+        let repaired = self.autocorrect_bytes_with_report(input);
+        self.check_result(&repaired.repairs, &repaired.violations)?;
+        Ok(repaired)
+    }
+
+    /// Like `try_autocorrect`, with the repaired JSON pretty-printed
+    pub fn try_autocorrect_pretty(&mut self, input: &str) -> Result<Repaired, FixError> {
+        self.check_input(input.as_bytes())?;
+        let mut repairs = Vec::new();
+        let value = self.correct(input.as_bytes(), &mut repairs);
+        let repaired = Repaired {
+            json: self.serializer.serialize_pretty(&value),
+            repairs,
+            violations: self.violations(&value),
         };
+        self.check_result(&repaired.repairs, &repaired.violations)?;
+        Ok(repaired)
+    }
+
+    /// Repair the input and deserialize it straight into `T`, without serializing in between
+    ///
+    /// Fails like `try_autocorrect`, or with `FixError::Deserialize` pointing at the value
+    /// in the original input that `T` could not accept.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: DeserializeOwned>(
+        &mut self,
+        input: &str,
+    ) -> Result<(T, Vec<Repair>), FixError> {
+        self.check_input(input.as_bytes())?;
+        let mut repairs = Vec::new();
+        let value = self.correct(input.as_bytes(), &mut repairs);
+        self.check_result(&repairs, &self.violations(&value))?;
 
-        // Step 5: Serialize back to JSON string
-        self.serializer.serialize(&result)
+        match serde_path_to_error::deserialize(value) {
+            Ok(value) => Ok((value, repairs)),
+            Err(err) => {
+                let segments: Vec<Segment> = err.path().iter().cloned().collect();
+                let tokens = self.located_tokens(input.as_bytes());
+                let span = de::locate(&tokens, &segments)
+                    .unwrap_or_else(|| Span::new(0, input.len(), 1, 1));
+                Err(FixError::Deserialize {
+                    path: de::pointer(&segments),
+                    message: err.into_inner().to_string(),
+                    span,
+                })
+            }
+        }
     }
 
-    /// Auto-correct and return pretty-printed JSON
-    pub fn autocorrect_pretty(&mut self, input: &str) -> String {
-        let tokens = self.tokenizer.tokenize(input);
-        let fixed_tokens = JSONTokenFixer::fix_tokens(tokens);
-        let parse_result = self.parser.parse(&fixed_tokens, 0);
+    /// Reject input that exceeds the size limit or holds nothing but whitespace
+    fn check_input(&self, input: &[u8]) -> Result<(), FixError> {
+        if let Some(limit) = self.options.input_size_limit() {
+            if input.len() > limit {
+                return Err(FixError::InputTooLarge {
+                    size: input.len(),
+                    limit,
+                });
+            }
+        }
+        if String::from_utf8_lossy(input).trim().is_empty() {
+            return Err(FixError::EmptyInput {
+                span: Span::new(0, input.len(), 1, 1),
+            });
+        }
+        Ok(())
+    }
+
+    /// Reject a result that needed disallowed or too many repairs, or misses the schema
+    /// Oversized input never gets here; `check_input` rejects it with its real size
+    fn check_result(&self, repairs: &[Repair], violations: &[Violation]) -> Result<(), FixError> {
+        if let Some(limit) = self.options.repair_limit() {
+            if let Some(repair) = repairs.get(limit) {
+                return Err(FixError::TooManyRepairs {
+                    limit,
+                    span: repair.span,
+                });
+            }
+        }
+        for repair in repairs {
+            let action = self.options.action(repair.kind);
+            match repair.kind {
+                RepairKind::EmptyFallback
+                    if matches!(action, RepairAction::Repair | RepairAction::Error) =>
+                {
+                    return Err(FixError::NoJsonFound { span: repair.span });
+                }
+                RepairKind::DepthLimited => {
+                    return Err(FixError::DepthLimitExceeded {
+                        limit: self.options.depth_limit().unwrap_or_default(),
+                        span: repair.span,
+                    });
+                }
+                RepairKind::TokenLimited => {
+                    return Err(FixError::TooManyTokens {
+                        limit: self.options.token_limit().unwrap_or_default(),
+                        span: repair.span,
+                    });
+                }
+                RepairKind::StringLimited => {
+                    return Err(FixError::StringTooLong {
+                        limit: self.options.string_length_limit().unwrap_or_default(),
+                        span: repair.span,
+                    });
+                }
+                _ if *action == RepairAction::Error => {
+                    return Err(FixError::RepairNotAllowed {
+                        kind: repair.kind,
+                        span: repair.span,
+                    });
+                }
+                _ => {}
+            }
+        }
 
-        let result = match parse_result.value {
+        if let Some(violation) = violations.first() {
+            return Err(FixError::SchemaMismatch {
+                path: violation.path.clone(),
+                message: violation.message.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Auto-correct broken JSON string and report every repair that was applied
+    pub fn autocorrect_with_report(&mut self, input: &str) -> Repaired {
+        self.autocorrect_bytes_with_report(input.as_bytes())
+    }
+
+    /// Auto-correct raw bytes that should contain UTF-8 JSON
+    /// Useful for streamed output where the last character may be cut off
+    pub fn autocorrect_bytes(&mut self, input: &[u8]) -> String {
+        self.autocorrect_bytes_with_report(input).json
+    }
+
+    /// Auto-correct raw bytes and report every repair that was applied
+    pub fn autocorrect_bytes_with_report(&mut self, input: &[u8]) -> Repaired {
+        let mut repairs = Vec::new();
+        let result = self.correct(input, &mut repairs);
+        self.report(&result, repairs)
+    }
+
+    /// Repair every top-level value in the input, such as `{"a":1}{"b":2}`
+    /// Separators between values are dropped and reported
+    pub fn autocorrect_all(&mut self, input: &str) -> Vec<String> {
+        self.autocorrect_all_with_report(input).values
+    }
+
+    /// Repair every top-level value in the input and report every repair that was applied
+    pub fn autocorrect_all_with_report(&mut self, input: &str) -> RepairedValues {
+        let mut repairs = Vec::new();
+        let input = self.limit_input(input.as_bytes(), &mut repairs);
+        let values = self.parse_all_tokens(input, &mut repairs);
+        self.finish_values(values, repairs)
+    }
+
+    /// Repair JSON Lines input record by record and return valid NDJSON
+    /// A record left open at the end of a line continues onto the following lines
+    pub fn autocorrect_lines(&mut self, input: &str) -> String {
+        self.autocorrect_lines_with_report(input).json
+    }
+
+    /// Repair JSON Lines input and report every repair that was applied
+    pub fn autocorrect_lines_with_report(&mut self, input: &str) -> Repaired {
+        self.autocorrect_lines_bytes_with_report(input.as_bytes())
+    }
+
+    /// Repair JSON Lines from raw bytes and report every repair that was applied
+    /// Invalid UTF-8 is replaced and reported as `RepairKind::InvalidUtf8`
+    pub fn autocorrect_lines_bytes_with_report(&mut self, input: &[u8]) -> Repaired {
+        let mut repairs = Vec::new();
+        let bytes = self.limit_input(input, &mut repairs);
+        let mut values = Vec::new();
+
+        for range in lines::split_records(bytes) {
+            let original = String::from_utf8_lossy(&bytes[range.clone()]).into_owned();
+            let span = extract::span_of(bytes, range);
+            let found = self.in_region(bytes, span, &mut repairs, Self::parse_all_tokens);
+            if found.is_empty() {
+                repairs.push(Repair::new(RepairKind::DroppedRecord, span, original, ""));
+            }
+            values.extend(found);
+        }
+
+        let repaired = self.finish_values(values, repairs);
+        let mut json = repaired.values.join("\n");
+        if !json.is_empty() {
+            json.push('\n');
+        }
+        Repaired {
+            json,
+            repairs: repaired.repairs,
+            violations: repaired.violations,
+        }
+    }
+
+    /// Find every JSON region embedded in prose or Markdown fences and repair each one
+    /// Regions from which no value could be recovered are skipped, as is input past the
+    /// size limit
+    pub fn extract(&mut self, input: &str) -> Vec<Extraction> {
+        let bytes = self.limit_input(input.as_bytes(), &mut Vec::new());
+        extract::find_regions(bytes)
+            .into_iter()
+            .filter_map(|region| {
+                let mut repairs = Vec::new();
+                let mut value =
+                    self.in_region(bytes, region.span, &mut repairs, Self::parse_tokens)?;
+                repairs.sort_by_key(|r| r.offset());
+                if self.options.sorts_keys() {
+                    value.sort_all_objects();
+                }
+                Some(Extraction {
+                    region,
+                    repaired: self.report(&value, repairs),
+                })
+            })
+            .collect()
+    }
+
+    /// Run the correction pipeline and return the recovered value
+    pub(crate) fn correct(&mut self, input: &[u8], repairs: &mut Vec<Repair>) -> Value {
+        let input = self.limit_input(input, repairs);
+        let parsed = if self.options.extracts_embedded() {
+            let regions = extract::find_regions(input);
+            extract::best_region(&regions).and_then(|region| {
+                let before = extract::span_of(input, 0..region.span.start);
+                let after = extract::span_of(input, region.span.end..input.len());
+                for span in [before, after] {
+                    let text = String::from_utf8_lossy(&input[span.start..span.end]);
+                    if !text.trim().is_empty() {
+                        let kind = RepairKind::RemovedSurroundingText;
+                        repairs.push(Repair::new(kind, span, text, ""));
+                    }
+                }
+                self.in_region(input, region.span, repairs, Self::parse_tokens)
+            })
+        } else {
+            self.parse_tokens(input, repairs)
+        };
+
+        self.finish(parsed, input, repairs)
+    }
+
+    /// Ignore input past the size limit, as if it had been cut off there
+    fn limit_input<'a>(&self, input: &'a [u8], repairs: &mut Vec<Repair>) -> &'a [u8] {
+        match self.options.input_size_limit() {
+            Some(limit) if input.len() > limit => {
+                let span = extract::span_of(input, limit..input.len());
+                repairs.push(Repair::new(RepairKind::InputLimited, span, "", ""));
+                &input[..limit]
+            }
+            _ => input,
+        }
+    }
+
+    /// Fall back to the configured default if nothing was recovered, then order the
+    /// repairs and apply key sorting
+    pub(crate) fn finish(
+        &self,
+        parsed: Option<Value>,
+        input: &[u8],
+        repairs: &mut Vec<Repair>,
+    ) -> Value {
+        // Get the result value or fall back to the configured default
+        let mut value = match parsed {
             Some(value) => value,
-            None => Value::Object(Map::new()),
+            None => {
+                let value = match self.options.action(RepairKind::EmptyFallback) {
+                    RepairAction::Drop => Value::Null,
+                    RepairAction::Substitute(value) => value.clone(),
+                    RepairAction::Repair | RepairAction::Error => Value::Object(Map::new()),
+                };
+                let span = Span::new(0, input.len(), 1, 1);
+                let original = String::from_utf8_lossy(input);
+                let replacement = self.serializer.serialize(&value);
+                repairs.push(Repair::new(RepairKind::EmptyFallback, span, original, replacement));
+                value
+            }
         };
 
-        self.serializer.serialize_pretty(&result)
+        // Report repairs in input order rather than pipeline order
+        repairs.sort_by_key(|r| r.offset());
+
+        if self.options.sorts_keys() {
+            value.sort_all_objects();
+        }
+        value
+    }
+
+    /// Run a pipeline on one region, reporting repairs in whole-input coordinates
+    fn in_region<T>(
+        &mut self,
+        input: &[u8],
+        span: Span,
+        repairs: &mut Vec<Repair>,
+        run: impl FnOnce(&mut Self, &[u8], &mut Vec<Repair>) -> T,
+    ) -> T {
+        let mut inner = Vec::new();
+        let result = run(self, &input[span.start..span.end], &mut inner);
+        repairs.extend(inner.into_iter().map(|repair| Repair {
+            span: repair.span.offset_by(span),
+            ..repair
+        }));
+        result
+    }
+
+    /// Balanced tokens of the region `correct` repairs, with spans in whole-input coordinates
+    #[cfg(feature = "serde")]
+    fn located_tokens(&mut self, input: &[u8]) -> Vec<Token> {
+        let region = if self.options.extracts_embedded() {
+            extract::best_region(&extract::find_regions(input)).map(|r| r.span)
+        } else {
+            None
+        };
+        let span = region.unwrap_or_else(|| Span::new(0, input.len(), 1, 1));
+
+        let bytes = &input[span.start..span.end];
+        let tokens = self.tokenizer.tokenize_bytes(bytes, &mut Vec::new());
+        JSONTokenFixer::fix_tokens_with_repairs(tokens, &mut Vec::new())
+            .into_iter()
+            .map(|token| {
+                let located = token.span.offset_by(span);
+                token.with_span(located)
+            })
+            .collect()
+    }
+
+    /// Tokenize and balance the input, then parse every top-level value in it
+    fn parse_all_tokens(&mut self, input: &[u8], repairs: &mut Vec<Repair>) -> Vec<Value> {
+        let tokens = self.tokenizer.tokenize_bytes(input, repairs);
+        let fixed_tokens = JSONTokenFixer::fix_tokens_with_repairs(tokens, repairs);
+
+        let mut values = Vec::new();
+        let mut idx = 0;
+        while let Some(token) = fixed_tokens.get(idx).filter(|t| t.kind != TokenKind::Eof) {
+            let result = self.parser.parse_with_repairs(&fixed_tokens, idx, repairs);
+            if result.index == idx {
+                // Separators between values, such as the comma in `{...},{...}`
+                repairs.push(Repair::new(RepairKind::DroppedToken, token.span, token.text(), ""));
+                idx += 1;
+            } else {
+                idx = result.index;
+            }
+            values.extend(result.value);
+        }
+        values
+    }
+
+    /// Order repairs, apply key sorting and serialize a list of recovered values
+    fn finish_values(&self, mut values: Vec<Value>, mut repairs: Vec<Repair>) -> RepairedValues {
+        repairs.sort_by_key(|r| r.offset());
+        if self.options.sorts_keys() {
+            values.iter_mut().for_each(Value::sort_all_objects);
+        }
+        let mut violations = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            violations.extend(self.violations(value).into_iter().map(|v| Violation {
+                path: format!("/{}{}", i, v.path),
+                ..v
+            }));
+        }
+        RepairedValues {
+            values: values.iter().map(|v| self.serializer.serialize(v)).collect(),
+            repairs,
+            violations,
+        }
+    }
+
+    /// Serialize a finished value and check it against the schema
+    pub(crate) fn report(&self, value: &Value, repairs: Vec<Repair>) -> Repaired {
+        Repaired {
+            json: self.serializer.serialize(value),
+            repairs,
+            violations: self.violations(value),
+        }
+    }
+
+    /// Places where the value does not match the configured schema
+    fn violations(&self, value: &Value) -> Vec<Violation> {
+        self.options
+            .json_schema()
+            .map_or_else(Vec::new, |schema| schema.validate(value))
+    }
+
+    /// Tokenize, balance and parse one value, discarding anything after it
+    fn parse_tokens(&mut self, input: &[u8], repairs: &mut Vec<Repair>) -> Option<Value> {
+        // Step 1: Tokenize the input
+        let tokens = self.tokenizer.tokenize_bytes(input, repairs);
+
+        // Step 2: Fix token stream (add missing brackets, etc.)
+        let fixed_tokens = JSONTokenFixer::fix_tokens_with_repairs(tokens, repairs);
+
+        self.parse_fixed(&fixed_tokens, input, repairs)
+    }
+
+    /// Parse the first value of a balanced token stream, discarding anything after it
+    pub(crate) fn parse_fixed(
+        &self,
+        fixed_tokens: &[Token],
+        input: &[u8],
+        repairs: &mut Vec<Repair>,
+    ) -> Option<Value> {
+        // Step 3: Parse tokens into JSON value
+        let parse_result = self.parser.parse_with_repairs(fixed_tokens, 0, repairs);
+
+        // Step 4: Anything after the first value is discarded
+        if let Some(rest) = fixed_tokens
+            .get(parse_result.index)
+            .filter(|t| t.kind != TokenKind::Eof)
+        {
+            let span = Span {
+                end: input.len(),
+                ..rest.span
+            };
+            repairs.push(Repair::new(
+                RepairKind::DroppedTrailingData,
+                span,
+                String::from_utf8_lossy(&input[span.start..]),
+                "",
+            ));
+        }
+
+        parse_result.value
     }
 }
 
@@ -67,6 +505,20 @@ impl Default for JSONAutoCorrector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::extract::RegionSource;
+    use crate::options::{
+        ArrayPairs, DuplicateKeys, NonFiniteNumbers, DEFAULT_MAX_DEPTH, MAX_DEPTH,
+    };
+    use crate::schema::Schema;
+
+    /// Repair each input with one corrector built from `options`
+    fn autocorrect_each<const N: usize>(
+        options: CorrectorOptions,
+        inputs: [&str; N],
+    ) -> [String; N] {
+        let mut corrector = JSONAutoCorrector::with_options(options);
+        inputs.map(|input| corrector.autocorrect(input))
+    }
 
     #[test]
     fn test_autocorrect_missing_brace() {
@@ -110,6 +562,387 @@ mod tests {
         assert_eq!(result, r#"{"value":null}"#);
     }
 
+    #[test]
+    fn test_autocorrect_escapes_round_trip() {
+        let mut corrector = JSONAutoCorrector::new();
+
+        #[cfg(feature = "preserve_order")]
+        {
+            let input = r#"{"q":"a\"b","nl":"x\ny","path":"c:\\tmp","u":"\u00e9"}"#;
+            let result = corrector.autocorrect(input);
+            assert_eq!(result, input.replace("\\u00e9", "é"));
+        }
+
+        let result = corrector.autocorrect(r#"{"s":"tab\	end\u12"#);
+        assert_eq!(result, r#"{"s":"tab\tend"}"#);
+    }
+
+    #[test]
+    #[cfg(feature = "preserve_order")]
+    fn test_autocorrect_non_ascii() {
+        let mut corrector = JSONAutoCorrector::new();
+
+        let result = corrector.autocorrect(r#"{"имя":"Привет, 世界 😀", город: Москва, "e":"é"#);
+        assert_eq!(result, r#"{"имя":"Привет, 世界 😀","город":"Москва","e":"é"}"#);
+    }
+
+    #[test]
+    fn test_autocorrect_bytes_truncated_utf8() {
+        let mut corrector = JSONAutoCorrector::new();
+
+        // Emoji cut off after three of its four bytes
+        let report = corrector.autocorrect_bytes_with_report(b"{\"a\":\"ok \xF0\x9F\x98");
+        assert_eq!(report.json, r#"{"a":"ok "}"#);
+        assert!(report.has_repair(RepairKind::TruncatedUtf8));
+        assert!(report.has_repair(RepairKind::ClosedString));
+
+        let report = corrector.autocorrect_bytes_with_report(b"[\"a\xFFb\", \xE4\xB8\x96\xE7]");
+        assert_eq!(report.json, "[\"a\u{fffd}b\",\"\u{4e16}\"]");
+        assert!(report.has_repair(RepairKind::InvalidUtf8));
+
+        // The fallible and JSON Lines variants count invalid UTF-8 as a repair too
+        let report = corrector.try_autocorrect_bytes(b"{\"a\":\"x\xFFy\"}").unwrap();
+        assert_eq!(report.json, "{\"a\":\"x\u{fffd}y\"}");
+        assert!(report.has_repair(RepairKind::InvalidUtf8));
+        let report = corrector.autocorrect_lines_bytes_with_report(b"[1]\n[\"\xFF\"]\n");
+        assert_eq!(report.json, "[1]\n[\"\u{fffd}\"]\n");
+        assert!(report.has_repair(RepairKind::InvalidUtf8));
+        assert!(matches!(
+            corrector.try_autocorrect_bytes(b" \n"),
+            Err(FixError::EmptyInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_autocorrect_numbers() {
+        let mut corrector = JSONAutoCorrector::new();
+
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            let input = r#"{"id":123456789012345678901234567890,"p":0.1000000000000000055}"#;
+            assert_eq!(corrector.autocorrect(input), input);
+
+            let input = r#"{"max":18446744073709551615,"min":-9223372036854775809}"#;
+            assert_eq!(corrector.autocorrect(input), input);
+        }
+
+        // A float would lose digits, so they are kept in a string
+        #[cfg(not(feature = "arbitrary_precision"))]
+        {
+            let input = r#"{"id":123456789012345678901234567890,"max":18446744073709551615}"#;
+            let report = corrector.autocorrect_with_report(input);
+            let expected = r#"{"id":"123456789012345678901234567890","max":18446744073709551615}"#;
+            assert_eq!(report.json, expected);
+            assert_eq!(report.repairs[0].kind, RepairKind::NumberToString);
+            assert_eq!(corrector.autocorrect("[1E5, 1.50, 1e400]"), r#"[100000.0,1.5,"1e400"]"#);
+        }
+
+        let result = corrector.autocorrect("[01, 1., .5, 1e, -, +5, 0x1F, 1_000]");
+        assert_eq!(result, "[1,1.0,0.5,1,0,5,31,1000]");
+    }
+
+    #[test]
+    fn test_autocorrect_strips_comments() {
+        let mut corrector = JSONAutoCorrector::new();
+        let input = "{\n  // id\n  \"url\": \"http://x/#a\", # tail\n  \"n\": 1 /* one */\n} /* unterminated";
+        let report = corrector.autocorrect_with_report(input);
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(report.json, r#"{"url":"http://x/#a","n":1}"#);
+        assert_eq!(report.repairs.len(), 4);
+        assert!(report.has_repair(RepairKind::RemovedComment));
+    }
+
+    #[test]
+    fn test_autocorrect_extracts_embedded_json() {
+        let options = CorrectorOptions::new().extract_embedded(true);
+        let mut corrector = JSONAutoCorrector::with_options(options);
+
+        let input = "Sure! Here is the data:\n```json\n{\"name\": \"Ann\", \"tags\": [\"a\"\n```\nLet me know!";
+        let report = corrector.autocorrect_with_report(input);
+        assert_eq!(report.json, r#"{"name":"Ann","tags":["a"]}"#);
+
+        let removed: Vec<&Repair> = report
+            .repairs
+            .iter()
+            .filter(|r| r.kind == RepairKind::RemovedSurroundingText)
+            .collect();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(removed[0].original, "Sure! Here is the data:\n```json\n");
+        assert_eq!(removed[1].original, "\n```\nLet me know!");
+
+        let closer = report
+            .repairs
+            .iter()
+            .find(|r| r.kind == RepairKind::InsertedCloser)
+            .unwrap();
+        assert_eq!(closer.span.start, input.find("\n```\nLet").unwrap());
+
+        let result = corrector.autocorrect("The answer is {\"ok\": tru} as requested.");
+        assert_eq!(result, r#"{"ok":true}"#);
+
+        let err = corrector.try_autocorrect("Sorry, I cannot help with that.").unwrap_err();
+        assert!(matches!(err, FixError::NoJsonFound { .. }));
+    }
+
+    #[test]
+    fn test_extract_all_regions() {
+        let mut corrector = JSONAutoCorrector::new();
+        let input = "First: {\"a\": 1}\nthen the list\n  [1, 2,] and finally {'b': tr";
+        let found = corrector.extract(input);
+
+        let json: Vec<&str> = found.iter().map(|e| e.repaired.json.as_str()).collect();
+        assert_eq!(json, vec![r#"{"a":1}"#, "[1,2]", r#"{"b":true}"#]);
+
+        assert_eq!(found[1].region.source, RegionSource::Balanced);
+        assert_eq!((found[1].region.span.line, found[1].region.span.column), (3, 3));
+        let comma = &found[1].repaired.repairs[0];
+        assert_eq!(comma.kind, RepairKind::RemovedTrailingComma);
+        assert_eq!(&input[comma.span.start..comma.span.end], ",");
+        assert_eq!((comma.span.line, comma.span.column), (3, 8));
+    }
+
+    #[test]
+    fn test_autocorrect_all_values() {
+        let mut corrector = JSONAutoCorrector::new();
+
+        let values = corrector.autocorrect_all(r#"{"a":1}{"b":2} [3, 4]"#);
+        assert_eq!(values, vec![r#"{"a":1}"#, r#"{"b":2}"#, "[3,4]"]);
+
+        let report = corrector.autocorrect_all_with_report(r#"{"a":1},{"b":tr"#);
+        assert_eq!(report.values, vec![r#"{"a":1}"#, r#"{"b":true}"#]);
+        assert_eq!(report.repairs[0].kind, RepairKind::DroppedToken);
+        assert_eq!(report.repairs[0].span.start, 7);
+
+        assert!(corrector.autocorrect_all("  ").is_empty());
+    }
+
+    #[test]
+    fn test_autocorrect_json_lines() {
+        let mut corrector = JSONAutoCorrector::new();
+        let input = concat!(
+            "{\"level\": \"info\", \"msg\": \"started\"}\n",
+            "{\"level\": \"warn\", \"msg\": \"cut off\n",
+            "\n",
+            "{\"level\": \"error\",\n",
+            "  \"ctx\": {\"retries\": 3,},\n",
+            "  \"msg\": \"multi-line\"}\n",
+            "{'level': 'debug'}{\"level\": \"trace\"}\n",
+        );
+        let report = corrector.autocorrect_lines_with_report(input);
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(
+            report.json,
+            concat!(
+                "{\"level\":\"info\",\"msg\":\"started\"}\n",
+                "{\"level\":\"warn\",\"msg\":\"cut off\"}\n",
+                "{\"level\":\"error\",\"ctx\":{\"retries\":3},\"msg\":\"multi-line\"}\n",
+                "{\"level\":\"debug\"}\n",
+                "{\"level\":\"trace\"}\n",
+            )
+        );
+
+        let comma = report
+            .repairs
+            .iter()
+            .find(|r| r.kind == RepairKind::RemovedTrailingComma)
+            .unwrap();
+        assert_eq!((comma.span.line, comma.span.column), (5, 23));
+        assert_eq!(&input[comma.span.start..comma.span.end], ",");
+
+        assert_eq!(corrector.autocorrect_lines("\n\n"), "");
+
+        // A bracket inside a single-quoted string leaves the record closed
+        let input = "{'a': '['}\n{\"b\": 2}\n";
+        assert_eq!(corrector.autocorrect_lines(input), "{\"a\":\"[\"}\n{\"b\":2}\n");
+
+        // A record with nothing to recover is removed and reported as such
+        let report = corrector.autocorrect_lines_with_report("{\"a\":1}\n@@@@\n{\"b\":2}\n");
+        assert_eq!(report.json, "{\"a\":1}\n{\"b\":2}\n");
+        let dropped = report.repairs.iter().find(|r| r.kind == RepairKind::DroppedRecord).unwrap();
+        assert_eq!((dropped.span.line, dropped.original.as_str()), (2, "@@@@"));
+    }
+
+    #[test]
+    fn test_autocorrect_python_and_js_syntax() {
+        let mut corrector = JSONAutoCorrector::new();
+
+        #[cfg(feature = "preserve_order")]
+        {
+            let result = corrector.autocorrect("{'name': 'O\\'Brien', 'ok': True, 'n': None}");
+            assert_eq!(result, r#"{"name":"O'Brien","ok":true,"n":null}"#);
+        }
+
+        let result = corrector.autocorrect("{a: 1, b: `x`, c: undefined, d: NaN, e: -Infinity}");
+        assert_eq!(result, r#"{"a":1,"b":"x","c":null,"d":null,"e":null}"#);
+
+        let options = CorrectorOptions::new().non_finite_numbers(NonFiniteNumbers::Error);
+        let mut strict = JSONAutoCorrector::with_options(options);
+        let err = strict.try_autocorrect("[1, NaN]").unwrap_err();
+        assert!(matches!(
+            err,
+            FixError::RepairNotAllowed {
+                kind: RepairKind::NonFiniteNumber,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_missing_value_actions() {
+        let inputs = [r#"{"a":, "b": 2}"#, r#"{"a": }"#, "[1,,2]", "[,1]", "[1,,]", r#"{"x": [,]}"#];
+        let fix = |action| autocorrect_each(CorrectorOptions::new().missing_values(action), inputs);
+
+        assert_eq!(
+            fix(RepairAction::Repair),
+            [r#"{"a":null,"b":2}"#, r#"{"a":null}"#, "[1,null,2]", "[null,1]", "[1,null]", r#"{"x":[null]}"#]
+        );
+        assert_eq!(
+            fix(RepairAction::Drop),
+            [r#"{"b":2}"#, "{}", "[1,2]", "[1]", "[1]", r#"{"x":[]}"#]
+        );
+        assert_eq!(
+            fix(RepairAction::Substitute(Value::from(""))),
+            [r#"{"a":"","b":2}"#, r#"{"a":""}"#, r#"[1,"",2]"#, r#"["",1]"#, r#"[1,""]"#, r#"{"x":[""]}"#]
+        );
+
+        // Elements after an empty slot are kept, and the slot is reported where it is
+        let input = r#"{"x": [1,,2], "y": 3}"#;
+        let repaired = JSONAutoCorrector::new().autocorrect_with_report(input);
+        assert_eq!(repaired.json, r#"{"x":[1,null,2],"y":3}"#);
+        assert_eq!(repaired.repairs.len(), 1);
+        assert_eq!(repaired.repairs[0].kind, RepairKind::MissingValue);
+        assert_eq!(repaired.repairs[0].offset(), 9);
+        assert_eq!(repaired.repairs[0].replacement, "null");
+
+        let options = CorrectorOptions::new().missing_values(RepairAction::Error);
+        let err = JSONAutoCorrector::with_options(options)
+            .try_autocorrect("[1,,2]")
+            .unwrap_err();
+        assert_eq!(err.offset(), 3);
+    }
+
+    #[test]
+    fn test_dropped_literals_remove_their_entry() {
+        // Dropping a value is not a missing value: the entry goes, whatever that policy says
+        let options = CorrectorOptions::new()
+            .partial_literals(RepairAction::Drop)
+            .missing_values(RepairAction::Substitute(Value::from(0)));
+        let mut corrector = JSONAutoCorrector::with_options(options);
+        assert_eq!(corrector.autocorrect("[tr, 1, fals]"), "[1]");
+        assert_eq!(corrector.autocorrect(r#"{"a": nul, "b": 1, "c":}"#), r#"{"b":1,"c":0}"#);
+    }
+
+    #[test]
+    fn test_duplicate_key_policies() {
+        let input = r#"{"a": {"x": 1}, "b": 2, "a": {"y": 3}}"#;
+        let cases = [
+            (DuplicateKeys::KeepFirst, r#"{"a":{"x":1},"b":2}"#),
+            (DuplicateKeys::KeepLast, r#"{"a":{"y":3},"b":2}"#),
+            (DuplicateKeys::Collect, r#"{"a":[{"x":1},{"y":3}],"b":2}"#),
+            (DuplicateKeys::Merge, r#"{"a":{"x":1,"y":3},"b":2}"#),
+            #[cfg(feature = "preserve_order")]
+            (DuplicateKeys::Rename, r#"{"a":{"x":1},"b":2,"a_2":{"y":3}}"#),
+        ];
+        for (policy, expected) in cases {
+            let options = CorrectorOptions::new().duplicate_keys(policy);
+            assert_eq!(autocorrect_each(options, [input]), [expected], "{:?}", policy);
+        }
+
+        // The repeat is reported at the later member, with the member it became
+        let options = CorrectorOptions::new().duplicate_keys(DuplicateKeys::Merge);
+        let repaired = JSONAutoCorrector::with_options(options).autocorrect_with_report(input);
+        assert_eq!(repaired.repairs.len(), 1);
+        let repair = &repaired.repairs[0];
+        assert_eq!(repair.kind, RepairKind::DuplicateKey);
+        assert_eq!(&input[repair.span.start..repair.span.end], r#""a": {"y": 3}"#);
+        assert_eq!(repair.original, r#""a":{"y":3}"#);
+        assert_eq!(repair.replacement, r#""a":{"x":1,"y":3}"#);
+
+        // Keys filled in by other repairs count as well
+        let repaired = JSONAutoCorrector::new().autocorrect_with_report(r#"{"a": 1, "a"}"#);
+        assert_eq!(repaired.json, r#"{"a":null}"#);
+        assert!(repaired.has_repair(RepairKind::DuplicateKey));
+
+        let options = CorrectorOptions::new().duplicate_keys(DuplicateKeys::Error);
+        let err = JSONAutoCorrector::with_options(options)
+            .try_autocorrect(input)
+            .unwrap_err();
+        match err {
+            FixError::RepairNotAllowed { kind, span } => {
+                assert_eq!(kind, RepairKind::DuplicateKey);
+                assert_eq!(span.start, 24);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_arrays_resync_after_garbage() {
+        let mut corrector = JSONAutoCorrector::new();
+        assert_eq!(corrector.autocorrect(r#"["a" @ "b", "c"]"#), r#"["a","b","c"]"#);
+        assert_eq!(corrector.autocorrect(r#"{"x": [1 @, 2], "y": 3}"#), r#"{"x":[1,2],"y":3}"#);
+
+        // A closer where an element belongs is garbage, not the end of the array
+        let input = r#"{"a": [1, }, 2], "b": 1}"#;
+        assert_eq!(corrector.autocorrect(input), r#"{"a":[1,2],"b":1}"#);
+        assert_eq!(corrector.autocorrect("[1, }, 2, [3 }, 4]"), "[1,2,[3,4]]");
+        // After an element it still ends the array whose closer went missing
+        assert_eq!(corrector.autocorrect(r#"{"a": [1, 2}"#), r#"{"a":[1,2]}"#);
+        assert_eq!(corrector.autocorrect(r#"[{"a": 1], 2"#), r#"[{"a":1}]"#);
+        // So does one that a member of the object around the array follows
+        assert_eq!(corrector.autocorrect(r#"{"a":[}, "b":1}"#), r#"{"a":[],"b":1}"#);
+        assert_eq!(corrector.autocorrect(r#"{"a":[1,2,}, "b":3}"#), r#"{"a":[1,2],"b":3}"#);
+        let repaired = corrector.autocorrect_with_report(r#"{"a":[}, "b":1}"#);
+        let kinds: Vec<RepairKind> = repaired.repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, [RepairKind::InsertedCloser, RepairKind::DroppedCloser]);
+        assert_eq!(repaired.repairs[1].offset(), 6);
+
+        let repaired = corrector.autocorrect_with_report(r#"["a" @ "b"]"#);
+        assert_eq!(repaired.repairs[0].kind, RepairKind::DroppedToken);
+        assert_eq!(repaired.repairs[0].offset(), 5);
+    }
+
+    #[test]
+    fn test_array_pair_policies() {
+        let inputs = [r#"["a": 1, "b": 2]"#, "[1, 2: 3, 4]", r#"[{"x": 1}, "y": [2]]"#];
+        let fix = |policy| autocorrect_each(CorrectorOptions::new().array_pairs(policy), inputs);
+        assert_eq!(
+            fix(ArrayPairs::Object),
+            [r#"{"a":1,"b":2}"#, r#"[1,{"2":3},4]"#, r#"[{"x":1},{"y":[2]}]"#]
+        );
+        assert_eq!(
+            fix(ArrayPairs::Wrap),
+            [r#"[{"a":1},{"b":2}]"#, r#"[1,{"2":3},4]"#, r#"[{"x":1},{"y":[2]}]"#]
+        );
+
+        // A pair without a value is filled in like an object member
+        assert_eq!(JSONAutoCorrector::new().autocorrect(r#"[1, "a":]"#), r#"[1,{"a":null}]"#);
+
+        // Keys of the new object follow the duplicate key policy
+        let options = CorrectorOptions::new().duplicate_keys(DuplicateKeys::Collect);
+        let mut corrector = JSONAutoCorrector::with_options(options);
+        assert_eq!(corrector.autocorrect(r#"["a": 1, "a": 2]"#), r#"{"a":[1,2]}"#);
+
+        let input = "[1, 2: 3]";
+        let repaired = JSONAutoCorrector::new().autocorrect_with_report(input);
+        assert_eq!(repaired.repairs.len(), 1);
+        let repair = &repaired.repairs[0];
+        assert_eq!(repair.kind, RepairKind::PairInArray);
+        assert_eq!(&input[repair.span.start..repair.span.end], "2: 3");
+        assert_eq!(repair.original, "2:3");
+        assert_eq!(repair.replacement, r#"{"2":3}"#);
+
+        let repaired = JSONAutoCorrector::new().autocorrect_with_report(r#"["a": 1]"#);
+        let replaced: Vec<_> = repaired.repairs.iter().map(|r| r.replacement.as_str()).collect();
+        assert_eq!(replaced, ["{", "}"]);
+
+        let options = CorrectorOptions::new().array_pairs(ArrayPairs::Error);
+        let err = JSONAutoCorrector::with_options(options)
+            .try_autocorrect(input)
+            .unwrap_err();
+        assert_eq!(err.offset(), 4);
+    }
+
     #[test]
     fn test_autocorrect_trailing_comma() {
         let mut corrector = JSONAutoCorrector::new();
@@ -123,5 +956,388 @@ mod tests {
         let result = corrector.autocorrect(r#"{"outer":{"inner":[1,2,3"#);
         assert_eq!(result, r#"{"outer":{"inner":[1,2,3]}}"#);
     }
+
+    #[test]
+    #[cfg(feature = "preserve_order")]
+    fn test_autocorrect_keeps_key_order() {
+        let mut corrector = JSONAutoCorrector::new();
+        let input = r#"{"zeta":1,"alpha":{"y":true,"b":null},"mid":[{"z":0,"a":1}]}"#;
+        assert_eq!(corrector.autocorrect(input), input);
+
+        let options = CorrectorOptions::new().sort_keys(true);
+        let mut corrector = JSONAutoCorrector::with_options(options);
+        assert_eq!(
+            corrector.autocorrect(input),
+            r#"{"alpha":{"b":null,"y":true},"mid":[{"a":1,"z":0}],"zeta":1}"#
+        );
+    }
+
+    #[test]
+    fn test_strict_and_permissive_policies() {
+        let input = r#"{"flag":tr, "name":bob"#;
+
+        let mut permissive = JSONAutoCorrector::new();
+        assert_eq!(
+            permissive.try_autocorrect(input).unwrap().json,
+            r#"{"flag":true,"name":"bob"}"#
+        );
+
+        let mut strict = JSONAutoCorrector::with_options(CorrectorOptions::strict());
+        let err = strict.try_autocorrect(input).unwrap_err();
+        assert_eq!(
+            err,
+            FixError::RepairNotAllowed {
+                kind: RepairKind::CompletedLiteral,
+                span: Span::new(8, 10, 1, 9),
+            }
+        );
+        assert!(strict.try_autocorrect(r#"{"ok":[1,2]}"#).is_ok());
+        // The infallible API still repairs
+        assert_eq!(strict.autocorrect(input), r#"{"flag":true,"name":"bob"}"#);
+    }
+
+    #[test]
+    fn test_custom_actions() {
+        let options = CorrectorOptions::new()
+            .identifiers(RepairAction::Error)
+            .unmatched_closers(RepairAction::Error)
+            .empty_fallback(RepairAction::Substitute(Value::Array(Vec::new())));
+        let mut corrector = JSONAutoCorrector::with_options(options);
+
+        assert!(corrector.try_autocorrect(r#"{"a":1}}"#).is_err());
+        // Closers that close nothing inside an array count as unmatched too
+        for input in ["[1}]", "[1, }, 2]", r#"{"a": [1, }, "b": 2}"#] {
+            let err = corrector.try_autocorrect(input).unwrap_err();
+            assert!(matches!(err, FixError::RepairNotAllowed { kind: RepairKind::DroppedCloser, .. }));
+        }
+        // One that closes the object after an array left open is not
+        let repaired = corrector.try_autocorrect(r#"{"a":[}"#).unwrap();
+        assert_eq!(repaired.json, r#"{"a":[]}"#);
+        assert_eq!(
+            repaired.repairs,
+            [Repair::new(RepairKind::InsertedCloser, Span::new(6, 6, 1, 7), "", "]")]
+        );
+        // Nor is the closer of an array dropped as a key, which goes with its opener
+        let repaired = corrector.try_autocorrect("{[}").unwrap();
+        assert_eq!(repaired.json, "{}");
+        let kinds: Vec<RepairKind> = repaired.repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, [RepairKind::DroppedToken]);
+        assert!(corrector.try_autocorrect(r#"{"a":bob}"#).is_err());
+        assert_eq!(corrector.try_autocorrect(r#"{"a":tr"#).unwrap().json, r#"{"a":true}"#);
+        assert_eq!(corrector.autocorrect("@@@"), "[]");
+    }
+
+    #[test]
+    fn test_try_autocorrect_errors() {
+        let mut corrector = JSONAutoCorrector::new();
+        assert!(matches!(
+            corrector.try_autocorrect("  \n"),
+            Err(FixError::EmptyInput { .. })
+        ));
+        assert!(matches!(
+            corrector.try_autocorrect("@#$%"),
+            Err(FixError::NoJsonFound { .. })
+        ));
+        // The infallible API still falls back to an empty object
+        assert_eq!(corrector.autocorrect("@#$%"), "{}");
+
+        let options = CorrectorOptions::new()
+            .max_depth(2)
+            .max_input_size(32)
+            .max_repairs(1);
+        let mut corrector = JSONAutoCorrector::with_options(options);
+
+        let err = corrector.try_autocorrect(r#"{"a":{"b":{"c":1}}}"#).unwrap_err();
+        assert_eq!(
+            err,
+            FixError::DepthLimitExceeded {
+                limit: 2,
+                span: Span::new(10, 17, 1, 11),
+            }
+        );
+        assert_eq!(err.offset(), 10);
+
+        let err = corrector.try_autocorrect(&format!("[{}]", "1,".repeat(20))).unwrap_err();
+        assert_eq!(err, FixError::InputTooLarge { size: 42, limit: 32 });
+
+        let err = corrector.try_autocorrect("[tr, fa").unwrap_err();
+        assert!(matches!(err, FixError::TooManyRepairs { limit: 1, .. }));
+        assert_eq!(err.offset(), 5);
+
+        assert_eq!(corrector.try_autocorrect("[1, 2").unwrap().json, "[1,2]");
+
+        let repaired = corrector.try_autocorrect_pretty(r#"{"a": [1]"#).unwrap();
+        assert_eq!(repaired.json, "{\n  \"a\": [\n    1\n  ]\n}");
+        assert_eq!(repaired.repairs.len(), 1);
+        let err = corrector.try_autocorrect_pretty("[tr, fa").unwrap_err();
+        assert!(matches!(err, FixError::TooManyRepairs { limit: 1, .. }));
+    }
+
+    #[test]
+    fn test_schema_guided_repair() {
+        let schema = Schema::from_json(
+            r#"{
+                "type": "object",
+                "required": ["id", "status", "retries"],
+                "properties": {
+                    "id": {"type": "integer"},
+                    "status": {"enum": ["active", "archived"]},
+                    "retries": {"type": "integer", "default": 3},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "owner": {"type": "object", "required": ["name"]}
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut corrector = JSONAutoCorrector::with_options(CorrectorOptions::new().schema(schema));
+
+        let report = corrector.autocorrect_with_report(r#"{"id": "42", "tags": 7, "status": "arch"#);
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(
+            report.json,
+            r#"{"id":42,"tags":["7"],"status":"archived","retries":3}"#
+        );
+        let kinds: Vec<RepairKind> = report.repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RepairKind::CoercedValue,
+                RepairKind::WrappedInArray,
+                RepairKind::CompletedEnum,
+                RepairKind::ClosedString,
+                RepairKind::InsertedCloser,
+                RepairKind::InsertedDefault,
+            ]
+        );
+        assert_eq!(report.repairs[0].original, r#""42""#);
+        assert!(report.violations.is_empty());
+
+        let report = corrector.autocorrect_with_report(r#"{"id": 1.5, "status": "active", "owner": {}}"#);
+        let violations: Vec<(&str, &str)> =
+            report.violations.iter().map(|v| (v.path.as_str(), v.message.as_str())).collect();
+        assert_eq!(
+            violations,
+            vec![
+                ("/id", "expected integer, found number"),
+                ("/owner", "missing required property \"name\""),
+            ]
+        );
+        assert!(!report.is_valid());
+
+        let err = corrector.try_autocorrect(r#"{"id": "x", "status": "active"}"#).unwrap_err();
+        assert_eq!(
+            err,
+            FixError::SchemaMismatch {
+                path: "/id".to_string(),
+                message: "expected integer, found string".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_schema_wraps_single_object_in_array() {
+        let schema = Schema::from_value(&serde_json::json!({
+            "type": "array",
+            "items": {"type": "object", "properties": {"n": {"type": "number"}}}
+        }))
+        .unwrap();
+        let mut corrector = JSONAutoCorrector::with_options(CorrectorOptions::new().schema(schema));
+
+        let report = corrector.autocorrect_with_report(r#"{"n": "2.5"}"#);
+        assert_eq!(report.json, r#"[{"n":2.5}]"#);
+        assert_eq!(
+            report.repairs[0],
+            Repair::new(RepairKind::WrappedInArray, Span::new(0, 0, 1, 1), "", "[")
+        );
+
+        let values = corrector.autocorrect_all_with_report(r#"[{"n": 1}] [{"n": true}]"#);
+        assert_eq!(values.violations[0].path, "/1/0/n");
+    }
+
+    #[test]
+    fn test_schema_places_missing_closers() {
+        let schema = Schema::from_value(&serde_json::json!({
+            "type": "object",
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": {"type": "integer"},
+                            "tags": {"type": "array", "items": {"type": "string"}}
+                        }
+                    }
+                },
+                "total": {"type": "integer"},
+                "meta": {"type": "object"}
+            }
+        }))
+        .unwrap();
+        let mut corrector = JSONAutoCorrector::with_options(CorrectorOptions::new().schema(schema));
+
+        // A member of the outer object closes the containers it was written inside
+        let cases = [
+            (
+                r#"{"items": [{"id": 1}, {"id": 2}, "total": 2}"#,
+                r#"{"items":[{"id":1},{"id":2}],"total":2}"#,
+            ),
+            (r#"{"items": [{"id": 1, "total": 2"#, r#"{"items":[{"id":1}],"total":2}"#),
+            #[cfg(feature = "preserve_order")]
+            (
+                r#"{"items": [{"id": 1, "tags": ["a", "total": 2, "meta": {}"#,
+                r#"{"items":[{"id":1,"tags":["a"]}],"total":2,"meta":{}}"#,
+            ),
+            // A closer written after the member no longer closes anything
+            #[cfg(feature = "preserve_order")]
+            (
+                r#"{"items": [{"id": 1}, "total": 2], "meta": {}}"#,
+                r#"{"items":[{"id":1}],"total":2,"meta":{}}"#,
+            ),
+            // Objects without declared properties keep every key
+            (r#"{"meta": {"a": 1, "total": 2"#, r#"{"meta":{"a":1,"total":2}}"#),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(corrector.autocorrect(input), expected, "{}", input);
+        }
+
+        let report = corrector.autocorrect_with_report(r#"{"items": [{"id": 1}, "total": 2"#);
+        assert_eq!(report.json, r#"{"items":[{"id":1}],"total":2}"#);
+        assert_eq!(
+            report.repairs,
+            vec![
+                Repair::new(RepairKind::InsertedCloser, Span::new(20, 20, 1, 21), "", "]"),
+                Repair::new(RepairKind::InsertedCloser, Span::new(32, 32, 1, 33), "", "}"),
+            ]
+        );
+
+        let report = corrector.autocorrect_with_report(r#"{"items": [1, "total": 2]}"#);
+        assert_eq!(report.json, r#"{"items":[1],"total":2}"#);
+        let kinds: Vec<RepairKind> = report.repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, vec![RepairKind::InsertedCloser, RepairKind::DroppedCloser]);
+
+        // Without a schema the closers follow the brackets
+        let mut corrector = JSONAutoCorrector::new();
+        assert_eq!(
+            corrector.autocorrect(r#"{"items": [{"id": 1}, "total": 2"#),
+            r#"{"items":[{"id":1},{"total":2}]}"#
+        );
+    }
+
+    #[test]
+    fn test_max_depth_is_stack_safe() {
+        // Limits are capped at a depth the whole pipeline handles on a small stack
+        let options = CorrectorOptions::new().max_depth(usize::MAX);
+        assert_eq!(options.depth_limit(), Some(MAX_DEPTH));
+        let run = std::thread::Builder::new().stack_size(1 << 20).spawn(move || {
+            let mut corrector = JSONAutoCorrector::with_options(options);
+            for (open, close) in [("[", "]"), (r#"{"a":"#, "}")] {
+                let report = corrector.autocorrect_with_report(&open.repeat(MAX_DEPTH + 1));
+                let (open, close) = (open.repeat(MAX_DEPTH), close.repeat(MAX_DEPTH));
+                assert_eq!(report.json, format!("{}null{}", open, close));
+                assert!(report.has_repair(RepairKind::DepthLimited));
+            }
+        });
+        run.unwrap().join().unwrap();
+    }
+
+    #[test]
+    fn test_limits_for_untrusted_input() {
+        // Deep nesting is cut at the default depth instead of overflowing the stack
+        let mut corrector = JSONAutoCorrector::new();
+        let report = corrector.autocorrect_with_report(&"[".repeat(100_000));
+        let depth = DEFAULT_MAX_DEPTH;
+        assert_eq!(report.json, format!("{}null{}", "[".repeat(depth), "]".repeat(depth)));
+        assert!(report.has_repair(RepairKind::DepthLimited));
+
+        let options = CorrectorOptions::new()
+            .max_input_size(24)
+            .max_tokens(8)
+            .max_string_length(3);
+        let mut corrector = JSONAutoCorrector::with_options(options);
+        assert_eq!(corrector.autocorrect(r#"{"id": "alphabet"}"#), r#"{"id":"alp"}"#);
+        assert_eq!(corrector.autocorrect("[1, 2, 3, 4, 5]"), "[1,2,3,4]");
+        assert_eq!(corrector.autocorrect(r#"{"a": 1, "b": 2, "c": 3, "d": 4}"#), r#"{"a":1,"b":2}"#);
+
+        let err = corrector.try_autocorrect(r#"{"id": "alphabet"}"#).unwrap_err();
+        assert_eq!(
+            err,
+            FixError::StringTooLong {
+                limit: 3,
+                span: Span::new(7, 17, 1, 8),
+            }
+        );
+        let err = corrector.try_autocorrect("[1, 2, 3, 4, 5]").unwrap_err();
+        assert!(matches!(err, FixError::TooManyTokens { limit: 8, .. }));
+        assert_eq!(err.offset(), 11);
+        // Whitespace and comments after the last token are not tokens
+        let options = CorrectorOptions::new().max_tokens(3);
+        let mut limited = JSONAutoCorrector::with_options(options);
+        assert_eq!(limited.try_autocorrect("[1]\n").unwrap().json, "[1]");
+        assert_eq!(limited.try_autocorrect("[1] // done\n").unwrap().json, "[1]");
+
+        let err = corrector.try_autocorrect(&"1".repeat(30)).unwrap_err();
+        assert_eq!(err, FixError::InputTooLarge { size: 30, limit: 24 });
+    }
+
+    #[test]
+    fn test_report_valid_input() {
+        let mut corrector = JSONAutoCorrector::new();
+        let report = corrector.autocorrect_with_report(r#"{"key":[1,2,3]}"#);
+        assert_eq!(report.json, r#"{"key":[1,2,3]}"#);
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn test_report_lists_repairs() {
+        let mut corrector = JSONAutoCorrector::new();
+        let report = corrector.autocorrect_with_report(r#"{"a":tr "b":[1,2}"#);
+        assert_eq!(report.json, r#"{"a":true,"b":[1,2]}"#);
+        assert_eq!(
+            report.repairs,
+            vec![
+                Repair::new(RepairKind::CompletedLiteral, Span::new(5, 7, 1, 6), "tr", "true"),
+                Repair::new(RepairKind::InsertedComma, Span::new(8, 8, 1, 9), "", ","),
+                Repair::new(RepairKind::InsertedCloser, Span::new(16, 16, 1, 17), "", "]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_trailing_data_and_fallback() {
+        let mut corrector = JSONAutoCorrector::new();
+
+        let report = corrector.autocorrect_with_report(r#"{"a":1} {"b":2}"#);
+        assert_eq!(report.json, r#"{"a":1}"#);
+        assert_eq!(
+            report.repairs,
+            vec![Repair::new(
+                RepairKind::DroppedTrailingData,
+                Span::new(8, 15, 1, 9),
+                r#"{"b":2}"#,
+                ""
+            )]
+        );
+
+        let report = corrector.autocorrect_with_report("@");
+        assert_eq!(report.json, "{}");
+        assert!(report.has_repair(RepairKind::DroppedToken));
+        assert!(report.has_repair(RepairKind::EmptyFallback));
+    }
+
+    #[test]
+    fn test_report_line_and_column() {
+        let mut corrector = JSONAutoCorrector::new();
+        let report = corrector.autocorrect_with_report("{\n  \"a\": 1,\n  \"b\": nul\n");
+        assert_eq!(report.json, r#"{"a":1,"b":null}"#);
+
+        let literal = &report.repairs[0];
+        assert_eq!(literal.kind, RepairKind::CompletedLiteral);
+        assert_eq!((literal.span.line, literal.span.column), (3, 8));
+
+        let closer = &report.repairs[1];
+        assert_eq!(closer.kind, RepairKind::InsertedCloser);
+        assert_eq!((closer.span.line, closer.span.column), (4, 1));
+    }
 }
 
@@ -0,0 +1,173 @@
+//! Command-line parsing for `jsonfix`
+
+use std::thread;
+
+pub const USAGE: &str = "\
+Usage: jsonfix [OPTIONS] [PATH...]
+
+Repairs broken JSON. PATH may be a file, a directory (searched recursively for
+.json, .jsonl and .ndjson files), a glob such as 'logs/**/*.json', or - for
+stdin. Without a PATH, stdin is read.
+
+Options:
+      --pretty          Indent the output
+      --check           Write nothing; only report through the exit code
+      --jsonl           Treat every input as JSON Lines (.jsonl and .ndjson always are)
+  -i, --in-place        Rewrite repaired files instead of printing them
+      --backup SUFFIX   Backup suffix for --in-place [default: .bak]
+      --no-backup       Do not keep a backup with --in-place
+  -s, --summary         Print one line per file and the totals to stderr
+  -j, --jobs N          Number of parallel workers [default: number of CPUs]
+  -h, --help            Print this help
+  -V, --version         Print the version
+
+Exit codes:
+  0  every input was valid JSON
+  1  at least one input was repaired
+  2  at least one input or JSON Lines record could not be recovered
+  3  usage or I/O error";
+
+/// Parsed command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Args {
+    /// Files, directories and globs; `-` is stdin
+    pub paths: Vec<String>,
+    pub pretty: bool,
+    pub check: bool,
+    pub jsonl: bool,
+    pub in_place: bool,
+    /// Suffix of the backup written next to each rewritten file
+    pub backup: Option<String>,
+    pub summary: bool,
+    pub jobs: usize,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            pretty: false,
+            check: false,
+            jsonl: false,
+            in_place: false,
+            backup: Some(".bak".to_string()),
+            summary: false,
+            jobs: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+}
+
+impl Args {
+    /// Whether repaired JSON goes to stdout
+    pub fn prints_output(&self) -> bool {
+        !self.check && !self.in_place
+    }
+}
+
+/// What the command line asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Run(Args),
+    Help,
+    Version,
+}
+
+/// Parse the arguments after the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| match inline {
+            Some(value) => Ok(value.to_string()),
+            None => args.next().ok_or_else(|| format!("{} needs a value", name)),
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--pretty" => parsed.pretty = true,
+            "--check" => parsed.check = true,
+            "--jsonl" => parsed.jsonl = true,
+            "-i" | "--in-place" => parsed.in_place = true,
+            "--backup" => parsed.backup = Some(value("--backup")?),
+            "--no-backup" => parsed.backup = None,
+            "-s" | "--summary" => parsed.summary = true,
+            "-j" | "--jobs" => parsed.jobs = jobs(&value("--jobs")?)?,
+            "--" => {
+                parsed.paths.extend(args);
+                break;
+            }
+            _ if flag.starts_with("-j") && flag.len() > 2 => parsed.jobs = jobs(&flag[2..])?,
+            _ if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option {}", flag));
+            }
+            _ => parsed.paths.push(arg),
+        }
+    }
+
+    if parsed.paths.is_empty() {
+        parsed.paths.push("-".to_string());
+    }
+    if parsed.in_place && parsed.check {
+        return Err("--in-place and --check cannot be combined".to_string());
+    }
+    if parsed.in_place && parsed.paths.iter().any(|p| p == "-") {
+        return Err("--in-place needs files, not stdin".to_string());
+    }
+    if parsed.pretty && parsed.jsonl {
+        return Err("--pretty cannot be combined with --jsonl".to_string());
+    }
+    Ok(Command::Run(parsed))
+}
+
+fn jobs(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("invalid number of jobs: {}", value)),
+        Ok(jobs) => Ok(jobs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> Result<Args, String> {
+        match parse(args.iter().map(|a| a.to_string()))? {
+            Command::Run(args) => Ok(args),
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        let args = run(&["--pretty", "-s", "-j4", "a.json", "--backup=.orig", "-i", "dir"]).unwrap();
+        assert!(args.pretty && args.summary && args.in_place);
+        assert_eq!(args.jobs, 4);
+        assert_eq!(args.backup.as_deref(), Some(".orig"));
+        assert_eq!(args.paths, ["a.json", "dir"]);
+
+        let args = run(&["--jobs", "2", "--no-backup", "--", "-odd.json"]).unwrap();
+        assert_eq!(args.jobs, 2);
+        assert_eq!(args.backup, None);
+        assert_eq!(args.paths, ["-odd.json"]);
+
+        // Without paths, stdin is read
+        assert_eq!(run(&["--check"]).unwrap().paths, ["-"]);
+        assert_eq!(parse(vec!["-V".to_string()]), Ok(Command::Version));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_usage() {
+        assert!(run(&["--frobnicate"]).is_err());
+        assert!(run(&["-j", "0"]).is_err());
+        assert!(run(&["--jobs"]).is_err());
+        assert!(run(&["--in-place", "--check", "a.json"]).is_err());
+        assert!(run(&["--in-place"]).is_err());
+        assert!(run(&["--pretty", "--jsonl"]).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+//! Expansion of command-line paths into the inputs to repair
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Extensions picked up when searching a directory
+const EXTENSIONS: [&str; 3] = ["json", "jsonl", "ndjson"];
+
+/// One input to repair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Input {
+    /// Whether the input is JSON Lines by its extension
+    pub fn is_json_lines(&self) -> bool {
+        match self {
+            Input::Stdin => false,
+            Input::File(path) => path
+                .extension()
+                .is_some_and(|ext| ext == "jsonl" || ext == "ndjson"),
+        }
+    }
+
+    /// Read the whole input as raw bytes, leaving invalid UTF-8 for the repair to report
+    pub fn read(&self) -> io::Result<Vec<u8>> {
+        match self {
+            Input::Stdin => {
+                let mut bytes = Vec::new();
+                io::stdin().lock().read_to_end(&mut bytes)?;
+                Ok(bytes)
+            }
+            Input::File(path) => fs::read(path),
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Stdin => f.write_str("<stdin>"),
+            Input::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Expand files, directories, globs and `-` into inputs, in command-line order
+pub fn expand(paths: &[String]) -> Result<Vec<Input>, String> {
+    let mut inputs = Vec::new();
+    for arg in paths {
+        let path = Path::new(arg);
+        if arg == "-" {
+            inputs.push(Input::Stdin);
+        } else if path.is_dir() {
+            let mut files = Vec::new();
+            walk(path, &mut files).map_err(|err| format!("{}: {}", arg, err))?;
+            inputs.extend(files.into_iter().map(Input::File));
+        } else if !path.exists() && has_wildcard(arg) {
+            let mut files = Vec::new();
+            glob(path, &mut files);
+            if files.is_empty() {
+                return Err(format!("{}: no files match", arg));
+            }
+            inputs.extend(files.into_iter().map(Input::File));
+        } else {
+            inputs.push(Input::File(path.to_path_buf()));
+        }
+    }
+    Ok(inputs)
+}
+
+/// Collect JSON files below `dir`, sorted by path and skipping hidden entries
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for path in sorted_entries(dir)? {
+        if is_hidden(&path) {
+            continue;
+        }
+        if path.is_dir() {
+            walk(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| EXTENSIONS.iter().any(|e| ext == *e))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn sorted_entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+fn has_wildcard(text: &str) -> bool {
+    text.contains(['*', '?'])
+}
+
+/// Collect the files matching a glob with `*`, `?` and `**` components
+/// Unreadable directories are skipped, like a shell does
+fn glob(pattern: &Path, files: &mut Vec<PathBuf>) {
+    let mut base = PathBuf::new();
+    let mut parts = Vec::new();
+    for component in pattern.components() {
+        let text = component.as_os_str().to_string_lossy();
+        if parts.is_empty() && !has_wildcard(&text) {
+            base.push(component);
+        } else {
+            parts.push(text.into_owned());
+        }
+    }
+    glob_in(&base, &parts, files);
+}
+
+fn glob_in(dir: &Path, parts: &[String], files: &mut Vec<PathBuf>) {
+    let Some((part, rest)) = parts.split_first() else {
+        return;
+    };
+    let listed = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let Ok(entries) = sorted_entries(listed) else {
+        return;
+    };
+
+    if part == "**" {
+        // Zero directories first, then every directory below
+        glob_in(dir, rest, files);
+        for entry in entries.iter().filter(|e| e.is_dir() && !is_hidden(e)) {
+            glob_in(&dir.join(entry.file_name().unwrap_or_default()), parts, files);
+        }
+        return;
+    }
+
+    for entry in entries {
+        let Some(name) = entry.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        // Like shells, wildcards do not match hidden files unless asked to
+        if (name.starts_with('.') && !part.starts_with('.')) || !matches(part, &name) {
+            continue;
+        }
+        let path = dir.join(&name);
+        if rest.is_empty() {
+            if path.is_file() {
+                files.push(path);
+            }
+        } else if path.is_dir() {
+            glob_in(&path, rest, files);
+        }
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Match one path component against a pattern with `*` and `?`
+fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it currently covers up to
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*.json", "a.json"));
+        assert!(matches("*.json", ".json"));
+        assert!(matches("log-??.json", "log-01.json"));
+        assert!(matches("*a*b*", "xxaxxbxx"));
+        assert!(matches("*", ""));
+        assert!(!matches("*.json", "a.jsonl"));
+        assert!(!matches("log-?.json", "log-01.json"));
+        assert!(!matches("a*b", "acbc"));
+    }
+
+    #[test]
+    fn test_expand_dirs_and_globs() {
+        let root = std::env::temp_dir().join(format!("jsonfix-inputs-{}", std::process::id()));
+        fs::create_dir_all(root.join("nested/deep")).unwrap();
+        for file in ["a.json", "b.txt", "nested/c.jsonl", "nested/deep/d.json", ".hidden.json"] {
+            fs::write(root.join(file), "{}").unwrap();
+        }
+        let file = |name: &str| Input::File(root.join(name));
+
+        let dir = root.to_string_lossy().into_owned();
+        assert_eq!(
+            expand(std::slice::from_ref(&dir)).unwrap(),
+            [file("a.json"), file("nested/c.jsonl"), file("nested/deep/d.json")]
+        );
+        assert_eq!(
+            expand(&[format!("{}/**/*.json", dir)]).unwrap(),
+            [file("a.json"), file("nested/deep/d.json")]
+        );
+        assert_eq!(
+            expand(&[format!("{}/*/c.*", dir), "-".to_string()]).unwrap(),
+            [file("nested/c.jsonl"), Input::Stdin]
+        );
+        assert!(file("nested/c.jsonl").is_json_lines());
+        assert!(expand(&[format!("{}/*.yaml", dir)]).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,391 @@
+//! `jsonfix`: repair broken JSON files from the command line
+
+mod args;
+mod inputs;
+
+use args::{Args, Command};
+use inputs::Input;
+use jsonfixer_rust::{JSONAutoCorrector, JSONSerializer, RepairKind};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Result of one input, ordered from best to worst; the exit code is the worst one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    Valid = 0,
+    Repaired = 1,
+    Unrecoverable = 2,
+    Failed = 3,
+}
+
+/// What happened to one input
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// The input was already valid; holds it re-serialized
+    Valid(String),
+    Repaired { json: String, repairs: usize },
+    /// Some JSON Lines records were recovered, but `lost` records could not be
+    Partial { json: String, lost: usize },
+    /// No trustworthy JSON could be recovered
+    Unrecoverable(String),
+    /// The input could not be read or the result could not be written
+    Failed(String),
+}
+
+impl Outcome {
+    fn status(&self) -> Status {
+        match self {
+            Outcome::Valid(_) => Status::Valid,
+            Outcome::Repaired { .. } => Status::Repaired,
+            Outcome::Partial { .. } | Outcome::Unrecoverable(_) => Status::Unrecoverable,
+            Outcome::Failed(_) => Status::Failed,
+        }
+    }
+
+    fn json(&self) -> Option<&str> {
+        match self {
+            Outcome::Valid(json)
+            | Outcome::Repaired { json, .. }
+            | Outcome::Partial { json, .. } => Some(json),
+            _ => None,
+        }
+    }
+
+    /// Free the JSON once nothing is going to print it
+    fn drop_json(&mut self) {
+        if let Outcome::Valid(json)
+        | Outcome::Repaired { json, .. }
+        | Outcome::Partial { json, .. } = self
+        {
+            *json = String::new();
+        }
+    }
+
+    /// One-line description for the summary
+    fn describe(&self) -> String {
+        match self {
+            Outcome::Valid(_) => "valid".to_string(),
+            Outcome::Repaired { repairs: 1, .. } => "repaired (1 repair)".to_string(),
+            Outcome::Repaired { repairs, .. } => format!("repaired ({} repairs)", repairs),
+            Outcome::Partial { lost: 1, .. } => "unrecoverable: 1 record lost".to_string(),
+            Outcome::Partial { lost, .. } => format!("unrecoverable: {} records lost", lost),
+            Outcome::Unrecoverable(reason) => format!("unrecoverable: {}", reason),
+            Outcome::Failed(reason) => format!("error: {}", reason),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let status = match args::parse(std::env::args().skip(1)) {
+        Ok(Command::Run(args)) => run(&args),
+        Ok(Command::Help) => {
+            println!("{}", args::USAGE);
+            Status::Valid
+        }
+        Ok(Command::Version) => {
+            println!("jsonfix {}", env!("CARGO_PKG_VERSION"));
+            Status::Valid
+        }
+        Err(message) => {
+            eprintln!("jsonfix: {}\n\n{}", message, args::USAGE);
+            Status::Failed
+        }
+    };
+    ExitCode::from(status as u8)
+}
+
+fn run(args: &Args) -> Status {
+    let inputs = match inputs::expand(&args.paths) {
+        Ok(inputs) => inputs,
+        Err(message) => {
+            eprintln!("jsonfix: {}", message);
+            return Status::Failed;
+        }
+    };
+    let mut stdout = io::stdout().lock();
+    let mut counts = [0usize; 4];
+    let mut worst = Status::Valid;
+    let finished = fix_all(&inputs, args, |input, outcome| {
+        counts[outcome.status() as usize] += 1;
+        worst = worst.max(outcome.status());
+        if args.prints_output() {
+            if let Some(json) = outcome.json() {
+                if let Err(err) = stdout.write_all(json.as_bytes()) {
+                    eprintln!("jsonfix: {}", err);
+                    return ControlFlow::Break(());
+                }
+            }
+        }
+        if args.summary {
+            eprintln!("{}: {}", input, outcome.describe());
+        } else if let Outcome::Unrecoverable(reason) | Outcome::Failed(reason) = &outcome {
+            eprintln!("jsonfix: {}: {}", input, reason);
+        } else if let Outcome::Partial { .. } = outcome {
+            eprintln!("jsonfix: {}: {}", input, outcome.describe());
+        }
+        ControlFlow::Continue(())
+    });
+    if finished.is_break() {
+        return Status::Failed;
+    }
+    if args.summary && inputs.len() > 1 {
+        eprintln!(
+            "{} files: {} valid, {} repaired, {} unrecoverable, {} failed",
+            inputs.len(),
+            counts[0],
+            counts[1],
+            counts[2],
+            counts[3]
+        );
+    }
+    worst
+}
+
+/// Process every input on up to `args.jobs` workers, handing each outcome to `report`
+/// in input order as soon as all earlier ones are done
+///
+/// Only outcomes that finish ahead of an earlier input are held back. Processing stops
+/// once `report` breaks.
+fn fix_all(
+    inputs: &[Input],
+    args: &Args,
+    mut report: impl FnMut(&Input, Outcome) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..args.jobs.clamp(1, inputs.len().max(1)) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                let mut corrector = JSONAutoCorrector::new();
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(input) = inputs.get(index) else {
+                        break;
+                    };
+                    let outcome = process(&mut corrector, input, args);
+                    if sender.send((index, outcome)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut waiting = HashMap::new();
+        let mut reported = 0;
+        for (index, outcome) in receiver.iter() {
+            waiting.insert(index, outcome);
+            while let Some(outcome) = waiting.remove(&reported) {
+                if report(&inputs[reported], outcome).is_break() {
+                    // Workers stop after their current input
+                    next.store(inputs.len(), Ordering::Relaxed);
+                    return ControlFlow::Break(());
+                }
+                reported += 1;
+            }
+        }
+        ControlFlow::Continue(())
+    })
+}
+
+/// Read, repair and, with `--in-place`, rewrite one input
+fn process(corrector: &mut JSONAutoCorrector, input: &Input, args: &Args) -> Outcome {
+    let bytes = match input.read() {
+        Ok(bytes) => bytes,
+        Err(err) => return Outcome::Failed(err.to_string()),
+    };
+    let jsonl = args.jsonl || input.is_json_lines();
+    let mut outcome = fix(corrector, &bytes, jsonl, args.pretty && !jsonl);
+
+    if let (true, Input::File(path), Outcome::Repaired { json, .. }) =
+        (args.in_place, input, &outcome)
+    {
+        if let Err(err) = write_in_place(path, json, args.backup.as_deref()) {
+            return Outcome::Failed(err.to_string());
+        }
+    }
+    if !args.prints_output() {
+        outcome.drop_json();
+    }
+    outcome
+}
+
+/// Repair one document, or one JSON Lines file record by record
+fn fix(corrector: &mut JSONAutoCorrector, input: &[u8], jsonl: bool, pretty: bool) -> Outcome {
+    let mut lost = 0;
+    let repaired = if jsonl {
+        let repaired = corrector.autocorrect_lines_bytes_with_report(input);
+        if repaired.json.is_empty() && !String::from_utf8_lossy(input).trim().is_empty() {
+            return Outcome::Unrecoverable("no JSON records found".to_string());
+        }
+        lost = repaired.repairs.iter().filter(|r| r.kind == RepairKind::DroppedRecord).count();
+        repaired
+    } else {
+        match corrector.try_autocorrect_bytes(input) {
+            Ok(repaired) => repaired,
+            Err(err) => return Outcome::Unrecoverable(err.to_string()),
+        }
+    };
+
+    let json = if pretty {
+        // The repaired JSON is valid, so this only changes the layout
+        match serde_json::from_str::<serde_json::Value>(&repaired.json) {
+            Ok(value) => JSONSerializer::new().serialize_pretty(&value) + "\n",
+            Err(err) => return Outcome::Failed(format!("repaired JSON is invalid: {}", err)),
+        }
+    } else if jsonl {
+        repaired.json
+    } else {
+        repaired.json + "\n"
+    };
+
+    // A file that lost records is never rewritten in place, since that would delete them
+    match repaired.repairs.len() {
+        _ if lost > 0 => Outcome::Partial { json, lost },
+        0 => Outcome::Valid(json),
+        repairs => Outcome::Repaired { json, repairs },
+    }
+}
+
+/// Replace the file with its repaired contents, keeping the original under `backup`
+/// The contents go to a temporary file first, so the original is never left half-written
+fn write_in_place(path: &Path, json: &str, backup: Option<&str>) -> io::Result<()> {
+    if let Some(suffix) = backup {
+        fs::copy(path, suffixed(path, suffix))?;
+    }
+    let temp = suffixed(path, &format!(".jsonfix-{}.tmp", std::process::id()));
+    let written = fs::write(&temp, json)
+        .and_then(|()| fs::set_permissions(&temp, fs::metadata(path)?.permissions()))
+        .and_then(|()| fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_classifies_inputs() {
+        let mut corrector = JSONAutoCorrector::new();
+
+        assert_eq!(
+            fix(&mut corrector, b"{\"a\": 1}", false, false),
+            Outcome::Valid("{\"a\":1}\n".to_string())
+        );
+        assert_eq!(
+            fix(&mut corrector, b"{a: 1", false, false),
+            Outcome::Repaired {
+                json: "{\"a\":1}\n".to_string(),
+                repairs: 2,
+            }
+        );
+        assert_eq!(fix(&mut corrector, b"   ", false, false).status(), Status::Unrecoverable);
+        // Invalid UTF-8 is a repair, not a valid input
+        assert_eq!(
+            fix(&mut corrector, b"{\"a\":\"x\xffy\"}", false, false),
+            Outcome::Repaired {
+                json: "{\"a\":\"x\u{fffd}y\"}\n".to_string(),
+                repairs: 1,
+            }
+        );
+
+        assert_eq!(
+            fix(&mut corrector, b"{\"a\": [1", false, true).json(),
+            Some("{\n  \"a\": [\n    1\n  ]\n}\n")
+        );
+        assert_eq!(
+            fix(&mut corrector, b"{\"a\": 1}\n{\"b\": 2\n", true, false),
+            Outcome::Repaired {
+                json: "{\"a\":1}\n{\"b\":2}\n".to_string(),
+                repairs: 1,
+            }
+        );
+        assert_eq!(fix(&mut corrector, b"", true, false), Outcome::Valid(String::new()));
+
+        // A JSON Lines record that yields nothing makes the whole input unrecoverable
+        let outcome = fix(&mut corrector, b"{\"a\":1}\n@@@@\n{\"b\":2}", true, false);
+        assert_eq!(
+            outcome,
+            Outcome::Partial {
+                json: "{\"a\":1}\n{\"b\":2}\n".to_string(),
+                lost: 1,
+            }
+        );
+        assert_eq!(outcome.status(), Status::Unrecoverable);
+    }
+
+    #[test]
+    fn test_in_place_keeps_backup_and_order() {
+        let root = std::env::temp_dir().join(format!("jsonfix-main-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let names = ["a.json", "b.json", "c.json", "d.json", "e.jsonl"];
+        let contents = ["{\"ok\": true}", "[1, 2", "", "{\"x\": nul}", "{\"a\":1}\n@@@@\n"];
+        for (name, content) in names.iter().zip(contents) {
+            fs::write(root.join(name), content).unwrap();
+        }
+
+        let args = Args {
+            paths: vec![root.to_string_lossy().into_owned()],
+            in_place: true,
+            jobs: 3,
+            ..Args::default()
+        };
+        let inputs = inputs::expand(&args.paths).unwrap();
+        let mut reported = Vec::new();
+        let finished = fix_all(&inputs, &args, |input, outcome| {
+            reported.push((input.clone(), outcome));
+            ControlFlow::Continue(())
+        });
+        assert!(finished.is_continue());
+        assert!(reported.iter().map(|(input, _)| input).eq(&inputs));
+        // Nothing prints in place, so no outcome holds on to its JSON
+        assert!(reported.iter().all(|(_, outcome)| outcome.json().is_none_or(str::is_empty)));
+        let statuses: Vec<_> = reported.iter().map(|(_, outcome)| outcome.status()).collect();
+        assert_eq!(
+            statuses,
+            [
+                Status::Valid,
+                Status::Repaired,
+                Status::Unrecoverable,
+                Status::Repaired,
+                Status::Unrecoverable
+            ]
+        );
+
+        let read = |name: &str| fs::read_to_string(root.join(name)).unwrap();
+        assert_eq!(read("a.json"), "{\"ok\": true}");
+        assert!(!root.join("a.json.bak").exists());
+        assert_eq!(read("b.json"), "[1,2]\n");
+        assert_eq!(read("b.json.bak"), "[1, 2");
+        assert_eq!(read("d.json"), "{\"x\":null}\n");
+        assert_eq!(read("c.json"), "");
+        // Lost records keep the file as it was
+        assert_eq!(read("e.jsonl"), "{\"a\":1}\n@@@@\n");
+        assert!(!root.join("e.jsonl.bak").exists());
+        // Rewritten files were renamed into place, leaving no temporary file behind
+        let names: Vec<_> = fs::read_dir(&root).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert!(names.iter().all(|name| !name.to_string_lossy().ends_with(".tmp")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+use crate::autocorrector::JSONAutoCorrector;
+use crate::error::FixError;
+use crate::events::push_escaped;
+use crate::report::Repair;
+use crate::token::{Span, Token, TokenKind};
+use serde::de::DeserializeOwned;
+use serde_path_to_error::Segment;
+
+/// Repair the input with the default policy and deserialize it into `T`
+///
+/// Returns the value together with every repair that was applied. Deserialization
+/// errors point at the offending value in the original input.
+pub fn from_str_lenient<T: DeserializeOwned>(input: &str) -> Result<(T, Vec<Repair>), FixError> {
+    JSONAutoCorrector::new().deserialize(input)
+}
+
+/// JSON pointer for a deserializer path
+pub(crate) fn pointer(segments: &[Segment]) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Seq { index } => path.push_str(&format!("/{}", index)),
+            Segment::Map { key } | Segment::Enum { variant: key } => {
+                path.push('/');
+                push_escaped(&mut path, key);
+            }
+            Segment::Unknown => path.push_str("/?"),
+        }
+    }
+    path
+}
+
+/// Span of the value at `segments` in a balanced token stream
+/// Stops at the deepest value that could be found, since repairs may have reshaped the rest
+pub(crate) fn locate(tokens: &[Token], segments: &[Segment]) -> Option<Span> {
+    let mut idx = tokens.iter().position(|t| t.kind != TokenKind::Eof)?;
+
+    for segment in segments {
+        let child = match (segment, tokens[idx].kind) {
+            (Segment::Seq { index }, TokenKind::LBracket) => element(tokens, idx, *index),
+            (Segment::Map { key }, TokenKind::LBrace) => member(tokens, idx, key),
+            _ => None,
+        };
+        match child {
+            Some(child) => idx = child,
+            None => break,
+        }
+    }
+
+    let end = skip_value(tokens, idx).saturating_sub(1).max(idx);
+    Some(Span {
+        end: tokens[end].span.end,
+        ..tokens[idx].span
+    })
+}
+
+/// Index of the `n`th element of the array opened at `open`
+fn element(tokens: &[Token], open: usize, n: usize) -> Option<usize> {
+    let mut idx = open + 1;
+    let mut count = 0;
+    while let Some(token) = tokens.get(idx) {
+        match token.kind {
+            TokenKind::RBracket | TokenKind::RBrace | TokenKind::Eof => return None,
+            TokenKind::Comma | TokenKind::Colon | TokenKind::Dropped | TokenKind::Stray => idx += 1,
+            _ if count == n => return Some(idx),
+            _ => {
+                count += 1;
+                idx = skip_value(tokens, idx);
+            }
+        }
+    }
+    None
+}
+
+/// Index of the value of `key` in the object opened at `open`
+/// The last occurrence wins, like in the parsed object under `DuplicateKeys::KeepLast`
+fn member(tokens: &[Token], open: usize, key: &str) -> Option<usize> {
+    let mut idx = open + 1;
+    let mut found = None;
+    while let Some(token) = tokens.get(idx) {
+        match token.kind {
+            TokenKind::RBrace | TokenKind::RBracket | TokenKind::Eof => break,
+            TokenKind::String if tokens.get(idx + 1).map(|t| t.kind) == Some(TokenKind::Colon) => {
+                let value = idx + 2;
+                if token.value.as_deref() == Some(key) {
+                    found = Some(value);
+                }
+                idx = skip_value(tokens, value);
+            }
+            _ => idx += 1,
+        }
+    }
+    found
+}
+
+/// Index just past the value starting at `idx`
+fn skip_value(tokens: &[Token], idx: usize) -> usize {
+    let mut open = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(idx) {
+        match token.kind {
+            TokenKind::LBrace | TokenKind::LBracket => open += 1,
+            TokenKind::RBrace | TokenKind::RBracket => {
+                if open == 0 {
+                    return i;
+                }
+                open -= 1;
+            }
+            TokenKind::Eof => return i,
+            _ => {}
+        }
+        if open == 0 {
+            return i + 1;
+        }
+    }
+    tokens.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::RepairKind;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Order {
+        id: u64,
+        items: Vec<Item>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        name: String,
+        qty: u32,
+    }
+
+    #[test]
+    fn test_from_str_lenient() {
+        let (order, repairs): (Order, _) =
+            from_str_lenient("{id: 7, 'items': [{\"name\": \"pen\", \"qty\": 2}").unwrap();
+        assert_eq!(
+            order,
+            Order {
+                id: 7,
+                items: vec![Item {
+                    name: "pen".to_string(),
+                    qty: 2,
+                }],
+            }
+        );
+        assert!(repairs.iter().any(|r| r.kind == RepairKind::QuotedIdentifier));
+        assert!(repairs.iter().any(|r| r.kind == RepairKind::InsertedCloser));
+    }
+
+    #[test]
+    fn test_from_str_lenient_error_location() {
+        let input = "{\"id\": 1, \"items\": [\n  {\"name\": \"pen\", \"qty\": 2},\n  {\"name\": \"ink\", \"qty\": \"many\"}";
+        let err = from_str_lenient::<Order>(input).unwrap_err();
+        match &err {
+            FixError::Deserialize { path, span, .. } => {
+                assert_eq!(path, "/items/1/qty");
+                assert_eq!(&input[span.start..span.end], "\"many\"");
+                assert_eq!((span.line, span.column), (3, 26));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        // A missing field points at the object that lacks it
+        let input = "[{\"name\": \"a\", \"qty\": 1}, {\"name\": \"b\"}]";
+        let err = from_str_lenient::<Vec<Item>>(input).unwrap_err();
+        assert_eq!(err.offset(), 26);
+        assert!(err.to_string().contains("missing field `qty`"));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::options::DuplicateKeys;
+use serde_json::{Map, Value};
+
+/// `"key":value` as it appears in compact output
+pub(crate) fn member(key: &str, value: &Value) -> String {
+    format!("{}:{}", Value::from(key), value)
+}
+
+/// Store `value` under `key`, which the object already holds, according to the policy
+/// `collected` holds the keys whose value is already an array of collected values.
+/// Returns the member as it ends up in the object.
+pub(crate) fn resolve(
+    policy: DuplicateKeys,
+    object: &mut Map<String, Value>,
+    collected: &mut Vec<String>,
+    key: String,
+    value: Value,
+) -> String {
+    match policy {
+        DuplicateKeys::KeepFirst => {}
+        DuplicateKeys::KeepLast | DuplicateKeys::Error => {
+            object.insert(key.clone(), value);
+        }
+        DuplicateKeys::Collect => {
+            let Some(slot) = object.get_mut(&key) else {
+                return String::new();
+            };
+            match slot {
+                Value::Array(items) if collected.contains(&key) => items.push(value),
+                _ => {
+                    *slot = Value::Array(vec![slot.take(), value]);
+                    collected.push(key.clone());
+                }
+            }
+        }
+        DuplicateKeys::Merge => {
+            if let Some(slot) = object.get_mut(&key) {
+                merge(slot, value);
+            }
+        }
+        DuplicateKeys::Rename => {
+            let renamed = renamed_key(object, &key);
+            let member = member(&renamed, &value);
+            object.insert(renamed, value);
+            return member;
+        }
+    }
+    object
+        .get(&key)
+        .map(|value| member(&key, value))
+        .unwrap_or_default()
+}
+
+/// First of `key_2`, `key_3`, ... that the object does not hold yet
+pub(crate) fn renamed_key(object: &Map<String, Value>, key: &str) -> String {
+    (2..)
+        .map(|n| format!("{}_{}", key, n))
+        .find(|renamed| !object.contains_key(renamed))
+        .unwrap_or_default()
+}
+
+/// Merge objects member by member; any other value replaces the target
+fn merge(target: &mut Value, value: Value) {
+    match (target, value) {
+        (Value::Object(target), Value::Object(value)) => {
+            for (key, value) in value {
+                match target.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, value) => *target = value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resolve_all(policy: DuplicateKeys, members: Vec<(&str, Value)>) -> (Value, Vec<String>) {
+        let mut object = Map::new();
+        let mut collected = Vec::new();
+        let mut resolved = Vec::new();
+        for (key, value) in members {
+            if object.contains_key(key) {
+                let key = key.to_string();
+                resolved.push(resolve(policy, &mut object, &mut collected, key, value));
+            } else {
+                object.insert(key.to_string(), value);
+            }
+        }
+        (Value::Object(object), resolved)
+    }
+
+    #[test]
+    fn test_resolve_policies() {
+        let members = || vec![("a", json!([1])), ("b", json!(0)), ("a", json!(2)), ("a", json!(3))];
+
+        let (value, resolved) = resolve_all(DuplicateKeys::KeepFirst, members());
+        assert_eq!(value, json!({"a": [1], "b": 0}));
+        assert_eq!(resolved, ["\"a\":[1]", "\"a\":[1]"]);
+
+        let (value, _) = resolve_all(DuplicateKeys::KeepLast, members());
+        assert_eq!(value, json!({"a": 3, "b": 0}));
+
+        // An array that was already there is collected as one value
+        let (value, resolved) = resolve_all(DuplicateKeys::Collect, members());
+        assert_eq!(value, json!({"a": [[1], 2, 3], "b": 0}));
+        assert_eq!(resolved, ["\"a\":[[1],2]", "\"a\":[[1],2,3]"]);
+
+        let (value, resolved) = resolve_all(DuplicateKeys::Rename, members());
+        assert_eq!(value, json!({"a": [1], "b": 0, "a_2": 2, "a_3": 3}));
+        assert_eq!(resolved, ["\"a_2\":2", "\"a_3\":3"]);
+    }
+
+    #[test]
+    fn test_merge_objects_deeply() {
+        let members = vec![
+            ("a", json!({"x": 1, "n": {"p": 1}})),
+            ("a", json!({"y": 2, "n": {"q": 2}, "x": 3})),
+        ];
+        let (value, _) = resolve_all(DuplicateKeys::Merge, members);
+        assert_eq!(value, json!({"a": {"x": 3, "n": {"p": 1, "q": 2}, "y": 2}}));
+
+        // Anything but two objects falls back to the last value
+        let members = vec![("a", json!({"x": 1})), ("a", json!(5))];
+        let (value, _) = resolve_all(DuplicateKeys::Merge, members);
+        assert_eq!(value, json!({"a": 5}));
+    }
+}
@@ -0,0 +1,84 @@
+use crate::report::RepairKind;
+use crate::token::Span;
+use thiserror::Error;
+
+/// Reasons the corrector refuses to produce output
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum FixError {
+    /// The input is empty or contains only whitespace
+    #[error("input is empty")]
+    EmptyInput { span: Span },
+    /// Nothing in the input could be recovered as a JSON value
+    #[error("no JSON value found at line {}, column {}", span.line, span.column)]
+    NoJsonFound { span: Span },
+    /// Objects and arrays are nested deeper than the configured limit
+    #[error("nesting deeper than {limit} at line {}, column {}", span.line, span.column)]
+    DepthLimitExceeded { limit: usize, span: Span },
+    /// The input is larger than the configured limit
+    #[error("input of {size} bytes exceeds the limit of {limit} bytes")]
+    InputTooLarge { size: usize, limit: usize },
+    /// The input holds more tokens than the configured limit
+    #[error("more than {limit} tokens, at line {}, column {}", span.line, span.column)]
+    TooManyTokens { limit: usize, span: Span },
+    /// A string is longer than the configured limit
+    #[error("string longer than {limit} bytes at line {}, column {}", span.line, span.column)]
+    StringTooLong { limit: usize, span: Span },
+    /// The input needs more repairs than the configured limit
+    #[error("more than {limit} repairs needed, at line {}, column {}", span.line, span.column)]
+    TooManyRepairs { limit: usize, span: Span },
+    /// The input needed a repair that the configured policy does not allow
+    #[error("{kind:?} repair not allowed at line {}, column {}", span.line, span.column)]
+    RepairNotAllowed { kind: RepairKind, span: Span },
+    /// The repaired value could not be deserialized into the requested type
+    #[error("{message} at \"{path}\", line {}, column {}", span.line, span.column)]
+    Deserialize {
+        /// JSON pointer to the rejected value
+        path: String,
+        message: String,
+        /// Location of the rejected value in the original input
+        span: Span,
+    },
+    /// The schema document could not be understood
+    #[error("invalid schema: {reason}")]
+    InvalidSchema { reason: String },
+    /// The repaired value still does not match the schema
+    #[error("schema mismatch at \"{path}\": {message}")]
+    SchemaMismatch { path: String, message: String },
+}
+
+impl FixError {
+    /// Location in the input where the problem was detected, if it has one
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            FixError::EmptyInput { span }
+            | FixError::NoJsonFound { span }
+            | FixError::DepthLimitExceeded { span, .. }
+            | FixError::TooManyTokens { span, .. }
+            | FixError::StringTooLong { span, .. }
+            | FixError::TooManyRepairs { span, .. }
+            | FixError::RepairNotAllowed { span, .. }
+            | FixError::Deserialize { span, .. } => Some(*span),
+            FixError::InputTooLarge { .. }
+            | FixError::InvalidSchema { .. }
+            | FixError::SchemaMismatch { .. } => None,
+        }
+    }
+
+    /// Byte offset in the input where the problem was detected
+    pub fn offset(&self) -> usize {
+        match self {
+            FixError::EmptyInput { span }
+            | FixError::NoJsonFound { span }
+            | FixError::DepthLimitExceeded { span, .. }
+            | FixError::TooManyTokens { span, .. }
+            | FixError::StringTooLong { span, .. }
+            | FixError::TooManyRepairs { span, .. }
+            | FixError::RepairNotAllowed { span, .. }
+            | FixError::Deserialize { span, .. } => span.start,
+            // Everything past the limit is rejected
+            FixError::InputTooLarge { limit, .. } => *limit,
+            // Not tied to a position in the input
+            FixError::InvalidSchema { .. } | FixError::SchemaMismatch { .. } => 0,
+        }
+    }
+}
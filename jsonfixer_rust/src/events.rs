@@ -0,0 +1,580 @@
+use crate::options::CorrectorOptions;
+use crate::parser::{scalar, JSONParser, ParseSink, ParseState};
+use crate::streaming::StreamingCorrector;
+use crate::token::Token;
+use serde_json::Value;
+
+/// Something that happened to the value being streamed, addressed by JSON pointer
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// An object member or array element began; its value is still to come
+    FieldStarted { path: String },
+    /// Text was appended to a string value that is still open
+    StringAppended { path: String, text: String },
+    /// Best guess for a value whose token may still grow, such as `tr` or `12`
+    PartialValue { path: String, value: Value },
+    /// A value is complete and will not change, unless its key is repeated later in the
+    /// same object, which `ValueReplaced` reports
+    /// Nested objects and arrays complete with `ContainerCompleted` instead; their whole
+    /// value only comes with the completion of the top-level value
+    ValueCompleted { path: String, value: Value },
+    /// A nested object or array closed; its members completed before it
+    ContainerCompleted { path: String },
+    /// A repeated key changed a value that already completed; `value` is what the object
+    /// holds now under the duplicate key policy. The repeat itself is not streamed
+    ValueReplaced { path: String, value: Value },
+}
+
+impl StreamEvent {
+    /// JSON pointer of the value this event is about
+    pub fn path(&self) -> &str {
+        match self {
+            StreamEvent::FieldStarted { path }
+            | StreamEvent::StringAppended { path, .. }
+            | StreamEvent::PartialValue { path, .. }
+            | StreamEvent::ValueCompleted { path, .. }
+            | StreamEvent::ContainerCompleted { path }
+            | StreamEvent::ValueReplaced { path, .. } => path,
+        }
+    }
+
+    /// True if the value completed and will not change any more, short of a repeated key
+    #[inline]
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            StreamEvent::ValueCompleted { .. } | StreamEvent::ContainerCompleted { .. }
+        )
+    }
+}
+
+/// Event-based view of streamed input, for rendering values as they arrive
+pub struct EventStream {
+    corrector: StreamingCorrector,
+}
+
+impl EventStream {
+    pub fn new() -> Self {
+        Self::with_options(CorrectorOptions::default())
+    }
+
+    /// Create an event stream with a custom repair policy
+    /// `extract_embedded` is ignored: events follow the input from its first byte
+    pub fn with_options(options: CorrectorOptions) -> Self {
+        Self {
+            corrector: StreamingCorrector::with_events(options),
+        }
+    }
+
+    /// Feed the next chunk and return the events it produced
+    pub fn push(&mut self, chunk: &str) -> Vec<StreamEvent> {
+        self.push_bytes(chunk.as_bytes())
+    }
+
+    /// Feed the next chunk of raw bytes and return the events it produced
+    pub fn push_bytes(&mut self, chunk: &[u8]) -> Vec<StreamEvent> {
+        self.corrector.push_bytes(chunk);
+        self.corrector.take_events()
+    }
+
+    /// Best-effort repaired JSON for everything pushed so far
+    pub fn snapshot(&mut self) -> String {
+        self.corrector.snapshot()
+    }
+
+    /// End the input: unfinished values are completed and open containers are closed
+    pub fn finish(mut self) -> Vec<StreamEvent> {
+        self.corrector.finish_events()
+    }
+}
+
+impl Default for EventStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Turns what the parser reports into events
+/// The parser builds and repairs the value; the sink only keeps track of the open string
+/// and of the member already reported as started
+struct EventSink {
+    events: Vec<StreamEvent>,
+    /// Pointer of the last `FieldStarted`, until a value completes
+    started: Option<String>,
+    /// The open string is a value, not a key
+    string_open: bool,
+}
+
+impl EventSink {
+    fn start_field(&mut self, path: String) {
+        if self.started.as_deref() != Some(path.as_str()) {
+            self.started = Some(path.clone());
+            self.events.push(StreamEvent::FieldStarted { path });
+        }
+    }
+
+    fn settle(&mut self) {
+        self.started = None;
+        self.string_open = false;
+    }
+}
+
+impl ParseSink for EventSink {
+    fn started(&mut self, path: String) {
+        self.start_field(path);
+    }
+
+    fn completed(&mut self, path: String, value: &Value, streamed: bool) {
+        self.settle();
+        // The top-level value completes with all of it
+        let event = if path.is_empty() {
+            StreamEvent::ValueCompleted {
+                path,
+                value: value.clone(),
+            }
+        } else {
+            completion(path, value, streamed)
+        };
+        self.events.push(event);
+    }
+
+    fn replaced(&mut self, path: String, value: &Value) {
+        self.settle();
+        let value = value.clone();
+        self.events.push(StreamEvent::ValueReplaced { path, value });
+    }
+}
+
+/// Parses balanced tokens as they are finished and reports what the parser finds as
+/// events, along with the string or scalar the input currently ends in
+pub(crate) struct EventParser {
+    parser: JSONParser,
+    state: ParseState,
+    sink: EventSink,
+}
+
+impl EventParser {
+    pub(crate) fn new(options: CorrectorOptions) -> Self {
+        Self {
+            parser: JSONParser::with_options(options),
+            state: ParseState::new(0),
+            sink: EventSink {
+                events: Vec::new(),
+                started: None,
+                string_open: false,
+            },
+        }
+    }
+
+    /// Events produced since the last call
+    pub(crate) fn take(&mut self) -> Vec<StreamEvent> {
+        std::mem::take(&mut self.sink.events)
+    }
+
+    /// Parse on over the finished tokens; repairs are reported by the corrector
+    pub(crate) fn advance(&mut self, tokens: &[Token]) {
+        let mut repairs = Vec::new();
+        self.parser.advance(&mut self.state, tokens, &mut repairs, &mut self.sink);
+    }
+
+    /// Parse the last tokens, which end the input, and complete whatever is still open
+    pub(crate) fn finish(&mut self, tokens: &[Token]) {
+        let mut repairs = Vec::new();
+        self.parser.finish(&mut self.state, tokens, &mut repairs, &mut self.sink);
+    }
+
+    /// A string started whose closing quote has not arrived yet
+    pub(crate) fn open_string(&mut self, tokens: &[Token]) {
+        if let Some(path) = self.parser.value_path(&self.state, tokens) {
+            self.sink.start_field(path);
+            self.sink.string_open = true;
+        }
+    }
+
+    /// More text was decoded for the open string
+    pub(crate) fn append(&mut self, tokens: &[Token], text: &str) {
+        if !self.sink.string_open || text.is_empty() {
+            return;
+        }
+        if let Some(path) = self.parser.value_path(&self.state, tokens) {
+            let text = text.to_string();
+            self.sink.events.push(StreamEvent::StringAppended { path, text });
+        }
+    }
+
+    /// The token at the end of input that may still grow
+    pub(crate) fn partial(&mut self, tokens: &[Token], token: &Token) {
+        let path = self.parser.value_path(&self.state, tokens);
+        if let (Some(value), Some(path)) = (scalar(token), path) {
+            self.sink.start_field(path.clone());
+            self.sink.events.push(StreamEvent::PartialValue { path, value });
+        }
+    }
+}
+
+/// Completion event for a value inside the top-level one
+fn completion(path: String, value: &Value, streamed: bool) -> StreamEvent {
+    match value {
+        Value::Object(_) | Value::Array(_) if streamed => StreamEvent::ContainerCompleted { path },
+        _ => StreamEvent::ValueCompleted {
+            path,
+            value: value.clone(),
+        },
+    }
+}
+
+/// Append a JSON pointer reference token, escaping `~` and `/`
+pub(crate) fn push_escaped(path: &mut String, key: &str) {
+    for c in key.chars() {
+        match c {
+            '~' => path.push_str("~0"),
+            '/' => path.push_str("~1"),
+            _ => path.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autocorrector::JSONAutoCorrector;
+    use crate::options::{ArrayPairs, DuplicateKeys, DEFAULT_MAX_DEPTH};
+    use crate::schema::Schema;
+    use serde_json::json;
+
+    fn started(path: &str) -> StreamEvent {
+        StreamEvent::FieldStarted {
+            path: path.to_string(),
+        }
+    }
+
+    fn appended(path: &str, text: &str) -> StreamEvent {
+        StreamEvent::StringAppended {
+            path: path.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    fn partial(path: &str, value: Value) -> StreamEvent {
+        StreamEvent::PartialValue {
+            path: path.to_string(),
+            value,
+        }
+    }
+
+    fn completed(path: &str, value: Value) -> StreamEvent {
+        StreamEvent::ValueCompleted {
+            path: path.to_string(),
+            value,
+        }
+    }
+
+    fn replaced(path: &str, value: Value) -> StreamEvent {
+        StreamEvent::ValueReplaced {
+            path: path.to_string(),
+            value,
+        }
+    }
+
+    fn closed(path: &str) -> StreamEvent {
+        StreamEvent::ContainerCompleted {
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_events_while_streaming() {
+        let mut stream = EventStream::new();
+
+        assert_eq!(
+            stream.push(r#"{"items": [{"na"#),
+            vec![started(""), started("/items"), started("/items/0")]
+        );
+        assert_eq!(
+            stream.push(r#"me": "Wid"#),
+            vec![started("/items/0/name"), appended("/items/0/name", "Wid")]
+        );
+        assert_eq!(stream.push("get\", \"ok\": tr"), vec![
+            appended("/items/0/name", "get"),
+            completed("/items/0/name", json!("Widget")),
+            started("/items/0/ok"),
+            partial("/items/0/ok", json!(true)),
+        ]);
+        assert!(!stream.push("u")[0].is_final());
+        assert_eq!(stream.push("e}, 4"), vec![
+            completed("/items/0/ok", json!(true)),
+            closed("/items/0"),
+            started("/items/1"),
+            partial("/items/1", json!(4)),
+        ]);
+        assert_eq!(stream.snapshot(), r#"{"items":[{"name":"Widget","ok":true},4]}"#);
+
+        let events = stream.finish();
+        assert_eq!(events, vec![
+            completed("/items/1", json!(4)),
+            closed("/items"),
+            completed("", json!({"items": [{"name": "Widget", "ok": true}, 4]})),
+        ]);
+        assert!(events.iter().all(StreamEvent::is_final));
+    }
+
+    #[test]
+    fn test_events_repairs_and_pointer_escaping() {
+        let mut stream = EventStream::new();
+        let mut events = stream.push(r#"{"a/b~c": 1 "flag", 'q': "open"#);
+        events.extend(stream.finish());
+
+        let finals: Vec<(&str, &Value)> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ValueCompleted { path, value } => Some((path.as_str(), value)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(finals, vec![
+            ("/a~1b~0c", &json!(1)),
+            ("/flag", &Value::Null),
+            ("/q", &json!("open")),
+            ("", &json!({"a/b~c": 1, "flag": null, "q": "open"})),
+        ]);
+    }
+
+    /// Every final or replacing event for the whole input under a duplicate key policy
+    fn completions(policy: DuplicateKeys, input: &str) -> Vec<StreamEvent> {
+        let options = CorrectorOptions::new().duplicate_keys(policy);
+        let mut stream = EventStream::with_options(options);
+        let mut events = stream.push(input);
+        events.extend(stream.finish());
+        events.retain(|e| e.is_final() || matches!(e, StreamEvent::ValueReplaced { .. }));
+        events
+    }
+
+    #[test]
+    fn test_events_rename_duplicate_keys() {
+        // Renamed keys are reported where the value ends up
+        let input = r#"{"a": 1, "a": 2, "a": 3}"#;
+        assert_eq!(completions(DuplicateKeys::Rename, input), vec![
+            completed("/a", json!(1)),
+            completed("/a_2", json!(2)),
+            completed("/a_3", json!(3)),
+            completed("", json!({"a": 1, "a_2": 2, "a_3": 3})),
+        ]);
+    }
+
+    #[test]
+    fn test_events_keep_first_ignores_repeats() {
+        let input = r#"{"a": 1, "a": 2, "a": 3}"#;
+        assert_eq!(completions(DuplicateKeys::KeepFirst, input), vec![
+            completed("/a", json!(1)),
+            completed("", json!({"a": 1})),
+        ]);
+
+        // Nothing of a repeat is streamed, since it may not end up anywhere
+        let options = CorrectorOptions::new().duplicate_keys(DuplicateKeys::KeepFirst);
+        let mut stream = EventStream::with_options(options);
+        let mut events = stream.push(r#"{"o": {"x": 1}, "o": {"x": "tw"#);
+        events.extend(stream.finish());
+        assert_eq!(events, vec![
+            started(""),
+            started("/o"),
+            started("/o/x"),
+            completed("/o/x", json!(1)),
+            closed("/o"),
+            completed("", json!({"o": {"x": 1}})),
+        ]);
+    }
+
+    #[test]
+    fn test_events_keep_last_replaces_the_value() {
+        let input = r#"{"a": 1, "b": 2, "a": [3]}"#;
+        assert_eq!(completions(DuplicateKeys::KeepLast, input), vec![
+            completed("/a", json!(1)),
+            completed("/b", json!(2)),
+            replaced("/a", json!([3])),
+            completed("", json!({"a": [3], "b": 2})),
+        ]);
+    }
+
+    #[test]
+    fn test_events_collect_reports_the_collection() {
+        let input = r#"{"a": 1, "a": 2, "a": 3}"#;
+        assert_eq!(completions(DuplicateKeys::Collect, input), vec![
+            completed("/a", json!(1)),
+            replaced("/a", json!([1, 2])),
+            replaced("/a", json!([1, 2, 3])),
+            completed("", json!({"a": [1, 2, 3]})),
+        ]);
+    }
+
+    #[test]
+    fn test_events_merge_reports_the_merged_object() {
+        let input = r#"{"o": {"x": 1}, "o": {"y": 2}}"#;
+        assert_eq!(completions(DuplicateKeys::Merge, input), vec![
+            completed("/o/x", json!(1)),
+            closed("/o"),
+            replaced("/o", json!({"x": 1, "y": 2})),
+            completed("", json!({"o": {"x": 1, "y": 2}})),
+        ]);
+    }
+
+    #[test]
+    fn test_events_fill_missing_values() {
+        let mut stream = EventStream::new();
+        let mut events = stream.push(r#"{"a":, "b": [,1,,]}"#);
+        events.extend(stream.finish());
+        assert_eq!(events, vec![
+            started(""),
+            started("/a"),
+            completed("/a", Value::Null),
+            started("/b"),
+            started("/b/0"),
+            completed("/b/0", Value::Null),
+            started("/b/1"),
+            completed("/b/1", json!(1)),
+            started("/b/2"),
+            completed("/b/2", Value::Null),
+            closed("/b"),
+            completed("", json!({"a": null, "b": [null, 1, null]})),
+        ]);
+    }
+
+    #[test]
+    fn test_events_wrap_array_pairs() {
+        let mut stream = EventStream::new();
+        let mut events = stream.push(r#"[1, "a": 2]"#);
+        events.extend(stream.finish());
+        assert_eq!(events, vec![
+            started(""),
+            started("/0"),
+            completed("/0", json!(1)),
+            started("/1"),
+            started("/1/a"),
+            completed("/1/a", json!(2)),
+            closed("/1"),
+            completed("", json!([1, {"a": 2}])),
+        ]);
+
+        // The final values agree with the batch result under either policy
+        let input = r#"{"p": ["x": 1, "x": [2], "y":], "q": ["z": {}, 3 @ 4, }, 5]}"#;
+        for policy in [ArrayPairs::Object, ArrayPairs::Wrap] {
+            let options = CorrectorOptions::new()
+                .array_pairs(policy)
+                .duplicate_keys(DuplicateKeys::Collect);
+            let mut stream = EventStream::with_options(options.clone());
+            let mut events = stream.push(input);
+            events.extend(stream.finish());
+            let expected = JSONAutoCorrector::with_options(options).autocorrect(input);
+            match events.last() {
+                Some(StreamEvent::ValueCompleted { value, .. }) => {
+                    assert_eq!(value.to_string(), expected)
+                }
+                other => panic!("unexpected event {:?}", other),
+            }
+
+            // A completed value is final, so no slot completes twice
+            let mut paths: Vec<_> = events
+                .iter()
+                .filter(|e| e.is_final())
+                .map(StreamEvent::path)
+                .collect();
+            let count = paths.len();
+            paths.sort_unstable();
+            paths.dedup();
+            assert_eq!(paths.len(), count);
+        }
+    }
+
+    #[test]
+    fn test_events_deep_nesting() {
+        // Containers past the depth limit become `null`, as in the batch result
+        let input = "[".repeat(20_000);
+        let mut stream = EventStream::new();
+        let mut events = stream.push(&input);
+        events.extend(stream.finish());
+        let expected = JSONAutoCorrector::new().autocorrect(&input);
+        match events.last() {
+            Some(StreamEvent::ValueCompleted { path, value }) => {
+                assert_eq!(path, "");
+                assert_eq!(value.to_string(), expected);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        let depth = DEFAULT_MAX_DEPTH;
+        let innermost = format!("/{}", vec!["0"; depth].join("/"));
+        assert!(events.contains(&completed(&innermost, Value::Null)));
+        // Nested containers complete without repeating their contents
+        let values = events.iter().filter(|e| matches!(e, StreamEvent::ValueCompleted { .. }));
+        assert_eq!(values.count(), 2);
+
+        let options = CorrectorOptions::new().max_depth(2);
+        let mut stream = EventStream::with_options(options);
+        let mut events = stream.push(r#"{"a": [{"b": [1]}, 2], "c": [[3]]}"#);
+        events.extend(stream.finish());
+        events.retain(StreamEvent::is_final);
+        assert_eq!(events, vec![
+            completed("/a/0", Value::Null),
+            completed("/a/1", json!(2)),
+            closed("/a"),
+            completed("/c/0", Value::Null),
+            closed("/c"),
+            completed("", json!({"a": [null, 2], "c": [null]})),
+        ]);
+    }
+
+    #[test]
+    fn test_events_follow_schema_and_depth_limit() {
+        let schema = Schema::from_value(&json!({
+            "type": "object",
+            "required": ["age", "retries"],
+            "properties": {
+                "age": {"type": "integer"},
+                "status": {"enum": ["active", "archived"]},
+                "retries": {"type": "integer", "default": 3},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "items": {
+                    "type": "array",
+                    "items": {"type": "object", "properties": {"id": {"type": "integer"}}}
+                },
+                "total": {"type": "integer"}
+            }
+        }))
+        .unwrap();
+        let with_schema = CorrectorOptions::new().schema(schema);
+        let cases = [
+            (with_schema.clone(), r#"{"age":"42"}"#),
+            (with_schema.clone(), r#"{"age": "7", "tags": 5, "status": "arch"#),
+            (with_schema.clone(), r#"{"retries":, "items": [{"id": "1"}, {"id": 2}, "total": 2}"#),
+            (with_schema.clone(), r#"{"items": [{"id": 1, "total": "3"], "age": 1}"#),
+            (with_schema, r#"{"items": [{"id": 1}, "total": 2"#),
+            (CorrectorOptions::new().max_depth(2), "[[[1]]]"),
+            (CorrectorOptions::new().max_depth(0), r#"{"a": 1}"#),
+        ];
+        for (options, input) in cases {
+            let expected = JSONAutoCorrector::with_options(options.clone()).autocorrect(input);
+            for size in [1, 3, input.len()] {
+                let mut stream = EventStream::with_options(options.clone());
+                let mut events: Vec<StreamEvent> = input
+                    .as_bytes()
+                    .chunks(size)
+                    .flat_map(|chunk| stream.push_bytes(chunk))
+                    .collect();
+                events.extend(stream.finish());
+                match events.last() {
+                    Some(StreamEvent::ValueCompleted { path, value }) if path.is_empty() => {
+                        assert_eq!(value.to_string(), expected, "{} in chunks of {}", input, size)
+                    }
+                    other => panic!("unexpected event {:?} for {}", other, input),
+                }
+                let mut paths: Vec<&str> =
+                    events.iter().filter(|e| e.is_final()).map(StreamEvent::path).collect();
+                let count = paths.len();
+                paths.sort_unstable();
+                paths.dedup();
+                assert_eq!(paths.len(), count, "{} in chunks of {}", input, size);
+            }
+        }
+
+        let options = CorrectorOptions::new().max_depth(2);
+        assert_eq!(JSONAutoCorrector::with_options(options).autocorrect("[[[1]]]"), "[[null]]");
+    }
+}
@@ -0,0 +1,210 @@
+use crate::report::Repaired;
+use crate::token::Span;
+use std::ops::Range;
+
+const FENCE: &[u8] = b"```";
+
+/// How an embedded JSON region was located
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionSource {
+    /// Contents of a Markdown code fence
+    Fence,
+    /// A balanced `{...}` or `[...]` run, or an unterminated one reaching the end of input
+    Balanced,
+}
+
+/// A stretch of free text that most likely holds JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// Location of the region in the original input
+    pub span: Span,
+    pub source: RegionSource,
+}
+
+impl Region {
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.span.start..self.span.end
+    }
+}
+
+/// One repaired region of free text
+#[derive(Debug, Clone)]
+pub struct Extraction {
+    pub region: Region,
+    /// Repaired JSON; repair spans point into the original input, not the region
+    pub repaired: Repaired,
+}
+
+/// Find every JSON candidate in free text, in input order
+/// Code fences win; balanced brackets are only searched when the text has no usable fence
+pub(crate) fn find_regions(bytes: &[u8]) -> Vec<Region> {
+    let fenced = fenced_ranges(bytes);
+    let (ranges, source) = if fenced.is_empty() {
+        (balanced_ranges(bytes, 0, bytes.len()), RegionSource::Balanced)
+    } else {
+        (fenced, RegionSource::Fence)
+    };
+
+    ranges
+        .into_iter()
+        .map(|range| Region {
+            span: span_of(bytes, range),
+            source,
+        })
+        .collect()
+}
+
+/// Pick the most likely region: the largest one, preferring the earliest on ties
+/// Truncated output usually leaves the payload unterminated, so validity is not required
+pub(crate) fn best_region(regions: &[Region]) -> Option<Region> {
+    regions
+        .iter()
+        .fold(None, |best: Option<&Region>, r| match best {
+            Some(b) if b.span.len() >= r.span.len() => Some(b),
+            _ => Some(r),
+        })
+        .copied()
+}
+
+/// Contents of ```` ``` ```` fences tagged as JSON, untagged, or starting with a bracket
+/// An unterminated fence runs to the end of input
+fn fenced_ranges(bytes: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+
+    while let Some(open) = find(bytes, FENCE, i) {
+        let tag_start = open + FENCE.len();
+        let tag_len = bytes[tag_start..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'+'))
+            .count();
+        let tag = &bytes[tag_start..tag_start + tag_len];
+        let content_start = tag_start + tag_len;
+        let close = find(bytes, FENCE, content_start).unwrap_or(bytes.len());
+
+        let content = trim(bytes, content_start, close);
+        let json_like = tag.is_empty()
+            || tag.to_ascii_lowercase().starts_with(b"json")
+            || matches!(bytes.get(content.start), Some(b'{' | b'['));
+        if !content.is_empty() && json_like {
+            ranges.push(content);
+        }
+        i = (close + FENCE.len()).min(bytes.len());
+    }
+    ranges
+}
+
+/// Top-level `{`/`[` runs between `from` and `to`
+/// Brackets inside double-quoted strings are ignored once a run has started
+fn balanced_ranges(bytes: &[u8], from: usize, to: usize) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut i = from;
+
+    while i < to {
+        if matches!(bytes[i], b'{' | b'[') {
+            let end = balanced_end(bytes, i, to);
+            ranges.push(i..end);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+/// Position after the bracket closing the one at `start`, or `to` if it never closes
+fn balanced_end(bytes: &[u8], start: usize, to: usize) -> usize {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut i = start;
+
+    while i < to {
+        match (in_string, bytes[i]) {
+            (true, b'\\') => i += 1,
+            (true, b'"') | (false, b'"') => in_string = !in_string,
+            (false, b'{' | b'[') => depth += 1,
+            (false, b'}' | b']') => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    to
+}
+
+#[inline]
+fn find(bytes: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| from + p)
+}
+
+#[inline]
+pub(crate) fn trim(bytes: &[u8], mut start: usize, mut end: usize) -> Range<usize> {
+    while start < end && bytes[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    start..end
+}
+
+/// Span of a byte range, with the line and column of its start
+pub(crate) fn span_of(bytes: &[u8], range: Range<usize>) -> Span {
+    let before = &bytes[..range.start];
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    let line_start = before.iter().rposition(|&b| b == b'\n').map_or(0, |p| p + 1);
+    // Count characters, not bytes: skip UTF-8 continuation bytes
+    let column = before[line_start..].iter().filter(|&&b| b & 0xC0 != 0x80).count() + 1;
+    Span::new(range.start, range.end, line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(input: &'a str, regions: &[Region]) -> Vec<&'a str> {
+        regions.iter().map(|r| &input[r.range()]).collect()
+    }
+
+    #[test]
+    fn test_find_fenced_regions() {
+        let input = "Sure! Here:\n```json\n{\"a\": 1}\n```\nand\n```python\nprint(1)\n```\n```\n[2, 3";
+        let regions = find_regions(input.as_bytes());
+
+        assert_eq!(texts(input, &regions), vec!["{\"a\": 1}", "[2, 3"]);
+        assert_eq!(regions[0].source, RegionSource::Fence);
+        assert_eq!((regions[0].span.line, regions[0].span.column), (3, 1));
+    }
+
+    #[test]
+    fn test_find_balanced_regions() {
+        let input = "See [1]. Data: {\"s\": \"} ]\", \"n\": [1, {}]} then {\"cut\": ";
+        let regions = find_regions(input.as_bytes());
+
+        assert_eq!(
+            texts(input, &regions),
+            vec!["[1]", "{\"s\": \"} ]\", \"n\": [1, {}]}", "{\"cut\": "]
+        );
+        assert!(regions.iter().all(|r| r.source == RegionSource::Balanced));
+        assert_eq!(regions[1].span.column, 16);
+    }
+
+    #[test]
+    fn test_best_region_prefers_largest() {
+        let input = "See [1] and {\"a\": [1, 2, 3], \"b\": {\"c\": 2";
+        let regions = find_regions(input.as_bytes());
+        let best = best_region(&regions).unwrap();
+        assert_eq!(&input[best.range()], "{\"a\": [1, 2, 3], \"b\": {\"c\": 2");
+
+        assert_eq!(best_region(&find_regions(b"no json here")), None);
+    }
+}
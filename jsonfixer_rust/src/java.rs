@@ -0,0 +1,648 @@
+//! JNI bindings for `com.github.dedinc.jsonfixer4j.JSONFixerRust`, built with the `jni` feature
+//!
+//! Every entry point catches panics and reports failures as Java exceptions, so a bug
+//! or bad input can never take down the JVM.
+
+use crate::{
+    ArrayPairs, CorrectorOptions, DuplicateKeys, FixError, JSONAutoCorrector, NonFiniteNumbers,
+    RepairAction, Span,
+};
+use jni::objects::{JClass, JObject, JString, JThrowable, JValue};
+use jni::sys::{jboolean, jint, jlong, jstring, JNI_FALSE};
+use jni::JNIEnv;
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+const FIX_EXCEPTION: &str = "com/github/dedinc/jsonfixer4j/JSONFixException";
+const FIX_EXCEPTION_INIT: &str = "(Ljava/lang/String;JII)V";
+
+thread_local! {
+    /// Default corrector of each Java thread, used by handle 0
+    /// Concurrent calls on the default handle never wait on each other
+    static CORRECTOR: RefCell<Handle> = RefCell::new(Handle::new());
+}
+
+/// Native state behind a `JSONFixerRust` instance
+/// The Java side guarantees that a handle is never used concurrently or after it is freed
+struct Handle {
+    options: CorrectorOptions,
+    pretty: bool,
+    /// Built on first use after the options change
+    corrector: Option<JSONAutoCorrector>,
+}
+
+impl Handle {
+    fn new() -> Self {
+        Self {
+            options: CorrectorOptions::new(),
+            pretty: false,
+            corrector: None,
+        }
+    }
+
+    fn configure(&mut self, f: impl FnOnce(CorrectorOptions) -> CorrectorOptions) {
+        self.options = f(std::mem::take(&mut self.options));
+        self.corrector = None;
+    }
+
+    fn autocorrect(&mut self, input: &str) -> String {
+        let pretty = self.pretty;
+        self.run(|corrector| {
+            if pretty {
+                corrector.autocorrect_pretty(input)
+            } else {
+                corrector.autocorrect(input)
+            }
+        })
+    }
+
+    fn try_autocorrect(&mut self, input: &str) -> Result<String, FixError> {
+        let pretty = self.pretty;
+        let repaired = self.run(|corrector| {
+            if pretty {
+                corrector.try_autocorrect_pretty(input)
+            } else {
+                corrector.try_autocorrect(input)
+            }
+        })?;
+        Ok(repaired.json)
+    }
+
+    /// Run `f` with the corrector, taken out while in use so a panic cannot leave a
+    /// half-updated corrector behind
+    fn run<T>(&mut self, f: impl FnOnce(&mut JSONAutoCorrector) -> T) -> T {
+        let mut corrector = match self.corrector.take() {
+            Some(corrector) => corrector,
+            None => JSONAutoCorrector::with_options(self.options.clone()),
+        };
+        let result = f(&mut corrector);
+        self.corrector = Some(corrector);
+        result
+    }
+}
+
+/// Why a native call produced no result
+enum Failure {
+    IllegalArgument(String),
+    Closed,
+    Fix(FixError),
+    Jni(jni::errors::Error),
+    Panic(String),
+}
+
+impl From<jni::errors::Error> for Failure {
+    fn from(err: jni::errors::Error) -> Self {
+        Failure::Jni(err)
+    }
+}
+
+/// Allocate a handle with the default options
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeCreate(
+    mut env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    guard(&mut env, 0, |_| Ok(Box::into_raw(Box::new(Handle::new())) as jlong))
+}
+
+/// Free a handle returned by `nativeCreate`
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeFree(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) {
+    guard(&mut env, (), |_| {
+        if handle != 0 {
+            // SAFETY: the handle came from `nativeCreate` and Java frees it only once
+            drop(unsafe { Box::from_raw(handle as *mut Handle) });
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeAutocorrect(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    input: JString,
+) -> jstring {
+    call(&mut env, handle, &input, |handle, input| Ok(handle.autocorrect(input)))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeTryAutocorrect(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    input: JString,
+) -> jstring {
+    call(&mut env, handle, &input, Handle::try_autocorrect)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeDefaultAction(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    action: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let action = repair_action(action)?;
+        h.configure(|o| o.default_action(action));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativePartialLiterals(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    action: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let action = repair_action(action)?;
+        h.configure(|o| o.partial_literals(action));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeIdentifiers(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    action: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let action = repair_action(action)?;
+        h.configure(|o| o.identifiers(action));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeMissingColon(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    action: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let action = repair_action(action)?;
+        h.configure(|o| o.missing_colon(action));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeMissingValues(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    action: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let action = repair_action(action)?;
+        h.configure(|o| o.missing_values(action));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeUnmatchedClosers(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    action: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let action = repair_action(action)?;
+        h.configure(|o| o.unmatched_closers(action));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeEmptyFallback(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    action: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let action = repair_action(action)?;
+        h.configure(|o| o.empty_fallback(action));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeNonFiniteNumbers(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    policy: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let policy = match policy {
+            0 => NonFiniteNumbers::Null,
+            1 => NonFiniteNumbers::String,
+            2 => NonFiniteNumbers::Error,
+            _ => return Err(Failure::IllegalArgument(format!("unknown policy {}", policy))),
+        };
+        h.configure(|o| o.non_finite_numbers(policy));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeDuplicateKeys(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    policy: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let policy = match policy {
+            0 => DuplicateKeys::KeepFirst,
+            1 => DuplicateKeys::KeepLast,
+            2 => DuplicateKeys::Collect,
+            3 => DuplicateKeys::Merge,
+            4 => DuplicateKeys::Rename,
+            5 => DuplicateKeys::Error,
+            _ => return Err(Failure::IllegalArgument(format!("unknown policy {}", policy))),
+        };
+        h.configure(|o| o.duplicate_keys(policy));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeArrayPairs(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    policy: jint,
+) {
+    configure(&mut env, handle, |h| {
+        let policy = match policy {
+            0 => ArrayPairs::Object,
+            1 => ArrayPairs::Wrap,
+            2 => ArrayPairs::Error,
+            _ => return Err(Failure::IllegalArgument(format!("unknown policy {}", policy))),
+        };
+        h.configure(|o| o.array_pairs(policy));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeSortKeys(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    enabled: jboolean,
+) {
+    configure(&mut env, handle, |h| {
+        h.configure(|o| o.sort_keys(enabled != JNI_FALSE));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeExtractEmbedded(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    enabled: jboolean,
+) {
+    configure(&mut env, handle, |h| {
+        h.configure(|o| o.extract_embedded(enabled != JNI_FALSE));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativePretty(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    enabled: jboolean,
+) {
+    configure(&mut env, handle, |h| {
+        h.pretty = enabled != JNI_FALSE;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeMaxDepth(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    limit: jlong,
+) {
+    configure(&mut env, handle, |h| {
+        let limit = limit_value(limit)?;
+        h.configure(|o| o.max_depth(limit));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeMaxInputSize(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    limit: jlong,
+) {
+    configure(&mut env, handle, |h| {
+        let limit = limit_value(limit)?;
+        h.configure(|o| o.max_input_size(limit));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeMaxTokens(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    limit: jlong,
+) {
+    configure(&mut env, handle, |h| {
+        let limit = limit_value(limit)?;
+        h.configure(|o| o.max_tokens(limit));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeMaxStringLength(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    limit: jlong,
+) {
+    configure(&mut env, handle, |h| {
+        let limit = limit_value(limit)?;
+        h.configure(|o| o.max_string_length(limit));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_nativeMaxRepairs(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    limit: jlong,
+) {
+    configure(&mut env, handle, |h| {
+        let limit = limit_value(limit)?;
+        h.configure(|o| o.max_repairs(limit));
+        Ok(())
+    })
+}
+
+/// `JSONFixerOptions.Action` ordinal as a repair action
+fn repair_action(ordinal: jint) -> Result<RepairAction, Failure> {
+    match ordinal {
+        0 => Ok(RepairAction::Repair),
+        1 => Ok(RepairAction::Drop),
+        2 => Ok(RepairAction::Error),
+        _ => Err(Failure::IllegalArgument(format!("unknown action {}", ordinal))),
+    }
+}
+
+fn limit_value(limit: jlong) -> Result<usize, Failure> {
+    usize::try_from(limit)
+        .map_err(|_| Failure::IllegalArgument(format!("limit must not be negative: {}", limit)))
+}
+
+/// Run `f` on the handle, or on the calling thread's default corrector for handle 0
+fn with_handle<T>(handle: jlong, f: impl FnOnce(&mut Handle) -> T) -> T {
+    if handle == 0 {
+        return CORRECTOR.with_borrow_mut(f);
+    }
+    // SAFETY: the handle came from `nativeCreate`, and Java serializes its use and
+    // never passes it after `nativeFree`
+    f(unsafe { &mut *(handle as *mut Handle) })
+}
+
+/// Change the options of a handle created by `nativeCreate`
+fn configure(
+    env: &mut JNIEnv,
+    handle: jlong,
+    f: impl FnOnce(&mut Handle) -> Result<(), Failure>,
+) {
+    guard(env, (), |_| match handle {
+        0 => Err(Failure::Closed),
+        _ => with_handle(handle, f),
+    })
+}
+
+/// Run `fix` on the Java string, turning every failure into a pending Java exception
+/// Returns `null` to Java when an exception was thrown
+fn call(
+    env: &mut JNIEnv,
+    handle: jlong,
+    input: &JString,
+    fix: impl FnOnce(&mut Handle, &str) -> Result<String, FixError>,
+) -> jstring {
+    guard(env, std::ptr::null_mut(), |env| {
+        if input.is_null() {
+            return Err(Failure::IllegalArgument("input must not be null".to_string()));
+        }
+        let input: String = env.get_string(input)?.into();
+        let output = with_handle(handle, |h| fix(h, &input)).map_err(Failure::Fix)?;
+        Ok(env.new_string(output)?.into_raw())
+    })
+}
+
+/// Run `f`, catching panics; on failure throw the matching Java exception and return
+/// `fallback`
+fn guard<T>(
+    env: &mut JNIEnv,
+    fallback: T,
+    f: impl FnOnce(&mut JNIEnv) -> Result<T, Failure>,
+) -> T {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(env)));
+    let failure = match result {
+        Ok(Ok(value)) => return value,
+        Ok(Err(failure)) => failure,
+        Err(payload) => Failure::Panic(panic_message(payload.as_ref())),
+    };
+    throw(env, failure);
+    fallback
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Throw the Java exception for a failure, unless one is already pending
+fn throw(env: &mut JNIEnv, failure: Failure) {
+    if env.exception_check().unwrap_or(true) {
+        return;
+    }
+    match failure {
+        Failure::IllegalArgument(message) => {
+            let _ = env.throw_new("java/lang/IllegalArgumentException", message);
+        }
+        Failure::Closed => {
+            let _ = env.throw_new("java/lang/IllegalStateException", "corrector is closed");
+        }
+        Failure::Fix(err) => throw_fix(env, &err.to_string(), err.span()),
+        Failure::Jni(err) => throw_fix(env, &format!("JNI error: {}", err), None),
+        Failure::Panic(message) => throw_fix(env, &format!("internal error: {}", message), None),
+    }
+}
+
+/// Throw `JSONFixException` with the byte offset, line and column of `span`
+/// Without a span the offset is -1 and line and column are 0
+fn throw_fix(env: &mut JNIEnv, message: &str, span: Option<Span>) {
+    let (offset, line, column) = span.map_or((-1, 0, 0), |span| {
+        (span.start as i64, span.line as i32, span.column as i32)
+    });
+    let exception = env.new_string(message).and_then(|message| {
+        let message = JObject::from(message);
+        let args = [
+            JValue::Object(&message),
+            JValue::Long(offset),
+            JValue::Int(line),
+            JValue::Int(column),
+        ];
+        env.new_object(FIX_EXCEPTION, FIX_EXCEPTION_INIT, &args)
+    });
+    match exception {
+        Ok(exception) => {
+            let _ = env.throw(JThrowable::from(exception));
+        }
+        Err(_) => {
+            // The exception class is missing or could not be built; fall back to a plain one
+            let _ = env.exception_clear();
+            let _ = env.throw_new("java/lang/RuntimeException", message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const INPUTS: [(&str, &str); 4] = [
+        (r#"{"key": 123"#, r#"{"key":123}"#),
+        (r#"{"arr": [1, 2, 3}"#, r#"{"arr":[1,2,3]}"#),
+        (r#"{"flag": tr, "value": nul}"#, r#"{"flag":true,"value":null}"#),
+        (r#"{"one": 1 "two": 2}"#, r#"{"one":1,"two":2}"#),
+    ];
+
+    /// Correct `per_thread` inputs on each of `threads` threads, checking every result
+    fn run_concurrently(threads: usize, per_thread: usize) -> Duration {
+        let start = Instant::now();
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                thread::spawn(move || {
+                    for i in 0..per_thread {
+                        let (input, expected) = INPUTS[(t + i) % INPUTS.len()];
+                        assert_eq!(with_handle(0, |h| h.autocorrect(input)), expected);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        start.elapsed()
+    }
+
+    #[test]
+    fn test_concurrent_corrections() {
+        run_concurrently(16, 500);
+        // The test thread has a corrector of its own, untouched by the workers
+        assert_eq!(with_handle(0, |h| h.autocorrect("[1, 2")), "[1,2]");
+    }
+
+    #[test]
+    fn test_default_handle_never_blocks() {
+        // Every thread holds its default corrector at the same time, which a shared
+        // corrector behind a lock would not allow
+        let threads = 8;
+        let inside = AtomicUsize::new(0);
+        let correctors = Mutex::new(Vec::new());
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| {
+                    with_handle(0, |h| {
+                        inside.fetch_add(1, Ordering::SeqCst);
+                        let deadline = Instant::now() + Duration::from_secs(10);
+                        while inside.load(Ordering::SeqCst) < threads {
+                            assert!(Instant::now() < deadline, "threads waited on each other");
+                            thread::yield_now();
+                        }
+                        correctors.lock().unwrap().push(h as *const Handle as usize);
+                        assert_eq!(h.autocorrect("[1, 2"), "[1,2]");
+                    })
+                });
+            }
+        });
+
+        let mut correctors = correctors.into_inner().unwrap();
+        correctors.sort_unstable();
+        correctors.dedup();
+        assert_eq!(correctors.len(), threads);
+    }
+
+    /// Throughput should grow with the thread count since no lock is shared
+    /// A timing benchmark: run with `cargo test --release -- --ignored` on four or more cores
+    #[test]
+    #[ignore]
+    fn bench_throughput_scales_with_threads() {
+        let per_thread = 20_000;
+        let single = run_concurrently(1, per_thread);
+        let cores = thread::available_parallelism().map_or(1, |n| n.get()).min(8);
+        let parallel = run_concurrently(cores, per_thread);
+
+        let speedup = cores as f64 * single.as_secs_f64() / parallel.as_secs_f64();
+        assert!(
+            cores < 4 || speedup > cores as f64 / 2.0,
+            "speedup of {:.1}x on {} cores: 1 thread took {:?}, {} threads took {:?}",
+            speedup,
+            cores,
+            single,
+            cores,
+            parallel
+        );
+    }
+
+    #[test]
+    fn test_handle_options() {
+        let mut handle = Handle::new();
+        handle.configure(|o| o.max_depth(1));
+        handle.pretty = true;
+        assert_eq!(handle.autocorrect("{\"a\": [1"), "{\n  \"a\": null\n}");
+        assert_eq!(handle.try_autocorrect("[1, 2").unwrap(), "[\n  1,\n  2\n]");
+
+        // Changing options rebuilds the corrector
+        handle.configure(|o| o.default_action(RepairAction::Error));
+        assert!(matches!(
+            handle.try_autocorrect("[1, 2"),
+            Err(FixError::RepairNotAllowed { .. })
+        ));
+        assert!(repair_action(3).is_err());
+        assert!(limit_value(-1).is_err());
+    }
+}
@@ -5,64 +5,99 @@
 //! missing brackets, braces, and other common JSON errors.
 //!
 //! ## Features
-//! - Fast tokenization using byte-level processing
+//! - Fast tokenization using byte-level processing with full UTF-8 support
 //! - Automatic bracket/brace matching and correction
 //! - Handles incomplete strings, literals, and numbers
+//! - Accepts Python and JavaScript literal syntax (`'single'` quotes, `None`, `NaN`)
+//! - Strips `//`, `#` and `/* */` comments
+//! - Extracts JSON embedded in prose or Markdown code fences
+//! - Recovers every top-level value and repairs JSON Lines record by record
+//! - Incremental correction of streamed output, chunk by chunk
+//! - JSON-pointer events for rendering partial values as they arrive
+//! - Schema-guided repair with a report of fields that still do not match
+//! - Lenient serde deserialization straight into typed structs
 //! - Memory-efficient parsing for large JSON objects
+//! - Byte, line and column spans for every token
+//! - Structured repair reports with the location of every fix
+//! - Configurable repair policy, from permissive to strict
+//! - Duplicate key policies: keep first or last, collect, deep-merge, rename or reject
+//! - Recovery from stray tokens and `key: value` pairs inside arrays
+//! - Limits on nesting depth, input size, token count and string length for untrusted input
 //! - JNI bindings for Java integration
+//!
+//! ## Usage
+//!
+//! ```
+//! use jsonfixer_rust::{CorrectorOptions, JSONAutoCorrector, RepairKind};
+//!
+//! let mut corrector = JSONAutoCorrector::new();
+//! assert_eq!(corrector.autocorrect("{'name': 'pen', qty: 2"), r#"{"name":"pen","qty":2}"#);
+//!
+//! // Strict mode refuses input that needs repairs, and says where
+//! let mut strict = JSONAutoCorrector::with_options(CorrectorOptions::strict());
+//! let err = strict.try_autocorrect("{qty: 2}").unwrap_err();
+//! assert_eq!(err.offset(), 1);
+//!
+//! // Every repair is reported with its location
+//! let repaired = corrector.autocorrect_with_report("[1, 2");
+//! assert_eq!(repaired.repairs[0].kind, RepairKind::InsertedCloser);
+//! ```
+//!
+//! ## Cargo Features
+//! - `serde` (default): `from_str_lenient` and `JSONAutoCorrector::deserialize`
+//! - `preserve_order`: keep object keys in input order instead of sorting them
+//! - `arbitrary_precision`: write numbers back exactly as they were written. Without it a
+//!   number a float cannot hold digit for digit becomes a string, reported as
+//!   `RepairKind::NumberToString`
+//! - `jni`: the native entry points used by the jsonfixer4j Java binding; turns on
+//!   `preserve_order` and `arbitrary_precision`
+//!
+//! `preserve_order` and `arbitrary_precision` turn on the `serde_json` features of the same
+//! name. Cargo unifies features, so they apply to every crate in your build that uses
+//! `serde_json`; with `arbitrary_precision`, numbers inside `#[serde(flatten)]` structs and
+//! untagged enums no longer deserialize into integer or float fields.
 
 mod autocorrector;
+#[cfg(feature = "serde")]
+mod de;
+mod duplicates;
+mod error;
+mod events;
+mod extract;
+#[cfg(feature = "jni")]
+mod java;
+mod lines;
+mod number;
+mod options;
 mod parser;
+mod report;
+mod schema;
 mod serializer;
+mod streaming;
 mod token;
 mod token_fixer;
 mod tokenizer;
 
 // Re-export main types
 pub use autocorrector::JSONAutoCorrector;
+#[cfg(feature = "serde")]
+pub use de::from_str_lenient;
+pub use error::FixError;
+pub use events::{EventStream, StreamEvent};
+pub use extract::{Extraction, Region, RegionSource};
+pub use options::{
+    ArrayPairs, CorrectorOptions, DuplicateKeys, NonFiniteNumbers, RepairAction, DEFAULT_MAX_DEPTH,
+    MAX_DEPTH,
+};
 pub use parser::{JSONParser, ParseResult};
+pub use report::{Repair, RepairKind, Repaired, RepairedValues, Violation};
+pub use schema::{JsonType, Schema};
 pub use serializer::JSONSerializer;
-pub use token::{Token, TokenKind};
-pub use token_fixer::JSONTokenFixer;
+pub use streaming::StreamingCorrector;
+pub use token::{Span, Token, TokenKind};
+pub use token_fixer::{BracketStack, JSONTokenFixer};
 pub use tokenizer::JSONTokenizer;
 
-// JNI bindings
-use jni::objects::{JClass, JString};
-use jni::sys::jstring;
-use jni::JNIEnv;
-use std::sync::Mutex;
-
-// Thread-local corrector for better performance in multi-threaded environments
-use once_cell::sync::Lazy;
-
-static CORRECTOR: Lazy<Mutex<JSONAutoCorrector>> = Lazy::new(|| {
-    Mutex::new(JSONAutoCorrector::new())
-});
-
-/// JNI entry point for Java integration
-/// Optimized to reuse corrector instance for better performance
-
-#[no_mangle]
-pub extern "system" fn Java_com_github_dedinc_jsonfixer4j_JSONFixerRust_autocorrect(
-    mut env: JNIEnv,
-    _class: JClass,
-    input: JString,
-) -> jstring {
-
-    let input: String = env
-        .get_string(&input)
-        .expect("Couldn't get Java string!")
-        .into();
-
-    // Use thread-safe shared corrector for better performance
-    let mut corrector = CORRECTOR.lock().unwrap();
-    let result = corrector.autocorrect(&input);
-
-    env.new_string(result)
-        .expect("Couldn't create Java string!")
-        .into_raw()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
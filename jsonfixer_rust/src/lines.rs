@@ -0,0 +1,129 @@
+use crate::extract::trim;
+use std::ops::Range;
+
+/// Bracket and string state of a record scanned so far
+#[derive(Default)]
+struct RecordState {
+    /// Open `{` and `[` brackets, innermost last
+    stack: Vec<u8>,
+    /// Quote of the string the scan is inside, `"`, `'` or a backtick
+    quote: Option<u8>,
+    escaped: bool,
+    /// Last non-whitespace byte outside strings
+    last: u8,
+}
+
+impl RecordState {
+    fn scan(&mut self, line: &[u8]) {
+        for &b in line {
+            if let Some(quote) = self.quote {
+                match b {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    _ if b == quote => self.quote = None,
+                    _ => {}
+                }
+                continue;
+            }
+            match b {
+                b'"' | b'\'' | b'`' => self.quote = Some(b),
+                b'{' | b'[' => self.stack.push(b),
+                b'}' | b']' => {
+                    self.stack.pop();
+                }
+                _ => {}
+            }
+            if !b.is_ascii_whitespace() {
+                self.last = b;
+            }
+        }
+    }
+
+    /// Whether a line starting with `first` continues this record rather than starting a new one
+    /// A line opening a container only continues a record that is waiting for a value,
+    /// so a record truncated mid-way does not swallow the next one
+    fn continues_into(&self, first: u8) -> bool {
+        if self.stack.is_empty() && self.quote.is_none() {
+            return false;
+        }
+        match first {
+            b'{' | b'[' => {
+                self.quote.is_none()
+                    && (self.last == b':'
+                        || (self.stack.last() == Some(&b'[') && matches!(self.last, b'[' | b',')))
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Split JSON Lines input into records, one per line
+/// A record whose brackets or strings are still open continues onto the following lines
+pub(crate) fn split_records(bytes: &[u8]) -> Vec<Range<usize>> {
+    let mut records = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    let mut state = RecordState::default();
+    let mut start = 0;
+
+    while start < bytes.len() {
+        let end = bytes[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(bytes.len(), |p| start + p);
+        let line = trim(bytes, start, end);
+        start = end + 1;
+
+        if line.is_empty() {
+            continue;
+        }
+        match current.as_mut() {
+            Some(record) if state.continues_into(bytes[line.start]) => record.end = line.end,
+            _ => {
+                records.extend(current.take());
+                state = RecordState::default();
+                current = Some(line.clone());
+            }
+        }
+        state.scan(&bytes[line]);
+    }
+    records.extend(current);
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(input: &str) -> Vec<&str> {
+        split_records(input.as_bytes())
+            .into_iter()
+            .map(|r| &input[r])
+            .collect()
+    }
+
+    #[test]
+    fn test_split_single_line_records() {
+        let input = "{\"a\":1}\r\n\n  {\"b\":2\n[3]\n";
+        assert_eq!(records(input), vec!["{\"a\":1}", "{\"b\":2", "[3]"]);
+    }
+
+    #[test]
+    fn test_split_multi_line_records() {
+        let input = "{\n  \"a\": [\n    {\"x\": 1},\n    {\"y\": \"two\n lines\"}\n  ],\n  \"b\":\n {}\n}\n{\"c\": [1,\n{\"d\": 4}";
+        assert_eq!(
+            records(input),
+            vec![
+                "{\n  \"a\": [\n    {\"x\": 1},\n    {\"y\": \"two\n lines\"}\n  ],\n  \"b\":\n {}\n}",
+                "{\"c\": [1,\n{\"d\": 4}",
+            ]
+        );
+
+        // A record cut off inside a string does not swallow the next record
+        let input = "{\"msg\": \"trunc\n{\"msg\": \"ok\"}";
+        assert_eq!(records(input), vec!["{\"msg\": \"trunc", "{\"msg\": \"ok\"}"]);
+
+        // Brackets inside single-quoted and backtick strings do not keep a record open
+        let input = "{'a': '['}\n{\"b\": `{`}\n[2]";
+        assert_eq!(records(input), vec!["{'a': '['}", "{\"b\": `{`}", "[2]"]);
+    }
+}
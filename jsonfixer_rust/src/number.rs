@@ -0,0 +1,201 @@
+use serde_json::Number;
+
+/// Rewrite a malformed number literal into a valid JSON number
+/// Handles leading zeros, missing integer or fraction digits, dangling exponents,
+/// explicit plus signs, digit separators and hex/octal/binary integers.
+/// Returns `None` if the text is not recognizably a number.
+pub(crate) fn repair_number(raw: &str) -> Option<String> {
+    let cleaned: String = raw.chars().filter(|&c| c != '_').collect();
+    let (sign, body) = match cleaned.as_bytes().first() {
+        Some(b'-') => ("-", &cleaned[1..]),
+        Some(b'+') => ("", &cleaned[1..]),
+        _ => ("", cleaned.as_str()),
+    };
+
+    // A lone sign, usually a number cut off at the end of input
+    if body.is_empty() {
+        return Some("0".to_string());
+    }
+
+    let radix = match body.get(..2) {
+        Some("0x") | Some("0X") => 16,
+        Some("0o") | Some("0O") => 8,
+        Some("0b") | Some("0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        let value = u128::from_str_radix(&body[2..], radix).ok()?;
+        let sign = if value == 0 { "" } else { sign };
+        return Some(format!("{}{}", sign, value));
+    }
+
+    let (int_part, rest) = split_digits(body);
+    let (fraction, rest) = match rest.strip_prefix('.') {
+        Some(rest) => {
+            let (fraction, rest) = split_digits(rest);
+            (Some(fraction), rest)
+        }
+        None => (None, rest),
+    };
+    let (exponent, rest) = match rest.strip_prefix(['e', 'E']) {
+        Some(rest) => {
+            let (exp_sign, rest) = match rest.strip_prefix(['+', '-']) {
+                Some(unsigned) => (&rest[..1], unsigned),
+                None => ("", rest),
+            };
+            let (digits, rest) = split_digits(rest);
+            (Some((exp_sign, digits)), rest)
+        }
+        None => (None, rest),
+    };
+
+    if !rest.is_empty() || (int_part.is_empty() && fraction.is_none_or(str::is_empty)) {
+        return None;
+    }
+
+    let mut result = String::with_capacity(raw.len() + 2);
+    result.push_str(sign);
+    match int_part.trim_start_matches('0') {
+        "" => result.push('0'),
+        digits => result.push_str(digits),
+    }
+    if let Some(fraction) = fraction {
+        result.push('.');
+        result.push_str(if fraction.is_empty() { "0" } else { fraction });
+    }
+    if let Some((exp_sign, digits)) = exponent.filter(|(_, digits)| !digits.is_empty()) {
+        result.push('e');
+        result.push_str(exp_sign);
+        result.push_str(digits);
+    }
+    Some(result)
+}
+
+/// Whether the text is already a valid JSON number
+pub(crate) fn is_valid_number(raw: &str) -> bool {
+    let body = raw.strip_prefix('-').unwrap_or(raw);
+    let (int_part, rest) = split_digits(body);
+    if int_part.is_empty() || (int_part.len() > 1 && int_part.starts_with('0')) {
+        return false;
+    }
+    let rest = match rest.strip_prefix('.') {
+        Some(rest) => match split_digits(rest) {
+            ("", _) => return false,
+            (_, rest) => rest,
+        },
+        None => rest,
+    };
+    match rest.strip_prefix(['e', 'E']) {
+        Some(rest) => {
+            let rest = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+            matches!(split_digits(rest), (digits, "") if !digits.is_empty())
+        }
+        None => rest.is_empty(),
+    }
+}
+
+/// A JSON number written back exactly as `raw` spells it, `1E5` included
+/// Returns `None` if the text is not a valid JSON number.
+#[cfg(feature = "arbitrary_precision")]
+pub(crate) fn exact(raw: &str) -> Option<Number> {
+    // Checked first, so surrounding whitespace or trailing text never gets through
+    if !is_valid_number(raw) {
+        return None;
+    }
+    Some(Number::from_string_unchecked(raw.to_string()))
+}
+
+/// A JSON number with the value `raw` spells, though not always its spelling: `1E5` is
+/// written back as `100000.0`
+/// Returns `None` if the text is not a valid JSON number or a float would lose digits.
+#[cfg(not(feature = "arbitrary_precision"))]
+pub(crate) fn exact(raw: &str) -> Option<Number> {
+    // Checked first, so surrounding whitespace or trailing text never gets through
+    if !is_valid_number(raw) {
+        return None;
+    }
+    let number: Number = serde_json::from_str(raw).ok()?;
+    let value = decimal(raw)?;
+    (decimal(&number.to_string())? == value).then_some(number)
+}
+
+/// Sign, significant digits and the power of ten of the last of them, for a valid number
+/// `1.50`, `15e-1` and `1.5` all come out the same
+#[cfg(not(feature = "arbitrary_precision"))]
+fn decimal(raw: &str) -> Option<(bool, String, i64)> {
+    let (negative, body) = match raw.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, raw),
+    };
+    let (mantissa, exponent) = match body.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => {
+            let exponent = exponent.strip_prefix('+').unwrap_or(exponent);
+            (mantissa, exponent.parse::<i64>().ok()?)
+        }
+        None => (body, 0),
+    };
+    let (int_part, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int_part, fraction);
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        return Some((negative, String::new(), 0));
+    }
+    let trailing = (digits.len() - significant.len()) as i64;
+    let exponent = exponent.checked_sub(fraction.len() as i64)?.checked_add(trailing)?;
+    Some((negative, significant.to_string(), exponent))
+}
+
+/// Split a string into its leading ASCII digits and the remainder
+#[inline]
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repair_malformed_numbers() {
+        let cases = [
+            ("01", "1"),
+            ("-007", "-7"),
+            ("1.", "1.0"),
+            (".5", "0.5"),
+            ("-.5", "-0.5"),
+            ("1e", "1"),
+            ("2.5E+", "2.5"),
+            ("-", "0"),
+            ("+5", "5"),
+            ("0x1F", "31"),
+            ("-0X1f", "-31"),
+            ("0b101", "5"),
+            ("0o17", "15"),
+            ("1_000", "1000"),
+            ("1_000.000_1e-3", "1000.0001e-3"),
+        ];
+
+        for (raw, expected) in cases {
+            assert_eq!(repair_number(raw).as_deref(), Some(expected), "input: {}", raw);
+        }
+    }
+
+    #[test]
+    fn test_is_valid_number() {
+        for valid in ["0", "-0", "42", "-3.25", "1e10", "1.5E-3", "0.0e+1"] {
+            assert!(is_valid_number(valid), "{}", valid);
+        }
+        for invalid in ["", "-", "01", "1.", ".5", "+1", "1e", "1e+", "0x1F", "1_000", "٣"] {
+            assert!(!is_valid_number(invalid), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_repair_rejects_non_numbers() {
+        for raw in [".", "1.2.3", "0xZZ", "-abc", "1e5e", "12ab"] {
+            assert_eq!(repair_number(raw), None, "input: {}", raw);
+        }
+    }
+}
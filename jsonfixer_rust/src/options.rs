@@ -0,0 +1,365 @@
+use crate::report::RepairKind;
+use crate::schema::Schema;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Nesting depth allowed by default, deep enough for real documents
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Largest nesting depth `max_depth` accepts
+/// Serializing and dropping values recurses once per level; this depth stays well within a
+/// 1 MiB thread stack, even in debug builds
+pub const MAX_DEPTH: usize = 512;
+
+/// What the corrector does when it runs into a particular kind of damage
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepairAction {
+    /// Apply the built-in repair
+    Repair,
+    /// Remove the offending token or entry
+    Drop,
+    /// Use the given value in place of the offending token
+    Substitute(Value),
+    /// Refuse the input; `try_autocorrect` returns an error
+    Error,
+}
+
+/// How `NaN`, `Infinity` and `-Infinity` are written, since JSON cannot represent them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFiniteNumbers {
+    /// Replace with `null`
+    #[default]
+    Null,
+    /// Keep the original spelling as a string
+    String,
+    /// Refuse the input; `try_autocorrect` returns an error
+    Error,
+}
+
+/// How a key repeated within one object is resolved
+/// Every repeat is reported as `RepairKind::DuplicateKey` with the resulting member
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Keep the first value and drop the later ones
+    KeepFirst,
+    /// Keep the last value, like most JSON parsers
+    #[default]
+    KeepLast,
+    /// Collect every value into an array, in input order
+    Collect,
+    /// Merge objects recursively; for any other value the last one wins
+    Merge,
+    /// Keep every value, storing repeats under `key_2`, `key_3` and so on
+    Rename,
+    /// Refuse the input; `try_autocorrect` returns an error
+    Error,
+}
+
+/// What happens to `key: value` pairs written inside an array, such as `[1, "a": 2]`
+/// Every pair is reported as `RepairKind::PairInArray`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayPairs {
+    /// Turn an array of nothing but pairs into an object, otherwise wrap each pair
+    #[default]
+    Object,
+    /// Wrap each pair in an object of its own: `[1, "a": 2]` becomes `[1, {"a": 2}]`
+    Wrap,
+    /// Refuse the input; `try_autocorrect` returns an error
+    Error,
+}
+
+/// Repair policy for `JSONAutoCorrector`
+///
+/// Every repair class defaults to `RepairAction::Repair`. `Drop` and `Substitute` are
+/// honoured for unquoted literals (partial literals, identifiers, malformed numbers),
+/// missing colons, missing values and the empty fallback; every other class only
+/// distinguishes `Error` from the built-in repair.
+#[derive(Debug, Clone)]
+pub struct CorrectorOptions {
+    default_action: RepairAction,
+    actions: HashMap<RepairKind, RepairAction>,
+    sort_keys: bool,
+    extract_embedded: bool,
+    non_finite: NonFiniteNumbers,
+    duplicate_keys: DuplicateKeys,
+    array_pairs: ArrayPairs,
+    max_depth: Option<usize>,
+    max_input_size: Option<usize>,
+    max_repairs: Option<usize>,
+    max_tokens: Option<usize>,
+    max_string_length: Option<usize>,
+    schema: Option<Arc<Schema>>,
+}
+
+impl CorrectorOptions {
+    /// Permissive policy: repair everything
+    pub fn new() -> Self {
+        Self {
+            default_action: RepairAction::Repair,
+            actions: HashMap::new(),
+            sort_keys: false,
+            extract_embedded: false,
+            non_finite: NonFiniteNumbers::Null,
+            duplicate_keys: DuplicateKeys::KeepLast,
+            array_pairs: ArrayPairs::Object,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_input_size: None,
+            max_repairs: None,
+            max_tokens: None,
+            max_string_length: None,
+            schema: None,
+        }
+    }
+
+    /// Strict policy: refuse any input that needs a repair
+    pub fn strict() -> Self {
+        Self::new().default_action(RepairAction::Error)
+    }
+
+    /// Permissive policy with limits suited to input from untrusted sources
+    /// Caps input at 8 MiB, tokens at one million, strings at 1 MiB and nesting at
+    /// `DEFAULT_MAX_DEPTH`
+    pub fn untrusted() -> Self {
+        Self::new()
+            .max_input_size(8 << 20)
+            .max_tokens(1_000_000)
+            .max_string_length(1 << 20)
+    }
+
+    /// Set the action for every kind of repair without its own action
+    pub fn default_action(mut self, action: RepairAction) -> Self {
+        self.default_action = action;
+        self
+    }
+
+    /// Set the action for one kind of repair
+    pub fn on(mut self, kind: RepairKind, action: RepairAction) -> Self {
+        self.actions.insert(kind, action);
+        self
+    }
+
+    /// Partial or miscased literals such as `tr` or `NULL`
+    /// `Drop` removes the literal, `Substitute` replaces it with the given value
+    pub fn partial_literals(self, action: RepairAction) -> Self {
+        self.on(RepairKind::CompletedLiteral, action)
+    }
+
+    /// Bare identifiers such as `{key: value}`
+    /// `Drop` removes the identifier, `Substitute` replaces it with the given value
+    pub fn identifiers(self, action: RepairAction) -> Self {
+        self.on(RepairKind::QuotedIdentifier, action)
+    }
+
+    /// Object keys without a `:` and value
+    /// `Repair` gives the key a `null` value, `Drop` removes the key,
+    /// `Substitute` gives the key the given value
+    pub fn missing_colon(self, action: RepairAction) -> Self {
+        self.on(RepairKind::MissingColon, action)
+    }
+
+    /// Keys followed by `:` but no value, as in `{"a":,}`, and empty array slots such as
+    /// `[1,,2]` or `[,1]`
+    /// `Repair` fills in `null`, `Drop` removes the key or slot, `Substitute` fills in the
+    /// given value
+    pub fn missing_values(self, action: RepairAction) -> Self {
+        self.on(RepairKind::MissingValue, action)
+    }
+
+    /// Closing brackets without a matching opener
+    /// The closer is always removed, only `Error` changes the outcome
+    pub fn unmatched_closers(self, action: RepairAction) -> Self {
+        self.on(RepairKind::DroppedCloser, action)
+    }
+
+    /// Input from which no value could be recovered
+    /// `Repair` returns `{}`, `Drop` returns `null`, `Substitute` returns the given value.
+    /// `try_autocorrect` reports `FixError::NoJsonFound` unless `Drop` or `Substitute` is set
+    pub fn empty_fallback(self, action: RepairAction) -> Self {
+        self.on(RepairKind::EmptyFallback, action)
+    }
+
+    /// `NaN`, `Infinity` and `-Infinity`
+    pub fn non_finite_numbers(mut self, non_finite: NonFiniteNumbers) -> Self {
+        self.non_finite = non_finite;
+        let action = match non_finite {
+            NonFiniteNumbers::Error => RepairAction::Error,
+            NonFiniteNumbers::Null | NonFiniteNumbers::String => RepairAction::Repair,
+        };
+        self.on(RepairKind::NonFiniteNumber, action)
+    }
+
+    /// Keys repeated within one object, `DuplicateKeys::KeepLast` unless set
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        let action = match duplicate_keys {
+            DuplicateKeys::Error => RepairAction::Error,
+            _ => RepairAction::Repair,
+        };
+        self.on(RepairKind::DuplicateKey, action)
+    }
+
+    /// `key: value` pairs inside arrays, `ArrayPairs::Object` unless set
+    pub fn array_pairs(mut self, array_pairs: ArrayPairs) -> Self {
+        self.array_pairs = array_pairs;
+        let action = match array_pairs {
+            ArrayPairs::Error => RepairAction::Error,
+            _ => RepairAction::Repair,
+        };
+        self.on(RepairKind::PairInArray, action)
+    }
+
+    /// Sort object keys alphabetically instead of keeping input order
+    /// Keys are sorted either way without the `preserve_order` feature
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Look for JSON embedded in prose or Markdown fences and repair only that region
+    /// The surrounding text is discarded and reported as `RemovedSurroundingText`
+    pub fn extract_embedded(mut self, extract_embedded: bool) -> Self {
+        self.extract_embedded = extract_embedded;
+        self
+    }
+
+    /// Steer repairs towards the given schema and report where the result still differs
+    /// Values are coerced, wrapped or completed to fit, and missing required properties
+    /// with a default are inserted. A member written inside a container that does not take
+    /// it, where an object around that container declares it, closes the container there
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(Arc::new(schema));
+        self
+    }
+
+    /// Maximum nesting depth of objects and arrays, `DEFAULT_MAX_DEPTH` unless set
+    /// Deeper containers are replaced with `null`; `try_autocorrect` fails instead.
+    /// Limits above `MAX_DEPTH` are lowered to it
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth.min(MAX_DEPTH));
+        self
+    }
+
+    /// Maximum input size in bytes
+    /// Input past the limit is ignored as if it had been cut off; `try_autocorrect` fails instead
+    pub fn max_input_size(mut self, max_input_size: usize) -> Self {
+        self.max_input_size = Some(max_input_size);
+        self
+    }
+
+    /// Maximum number of repairs `try_autocorrect` applies before giving up
+    pub fn max_repairs(mut self, max_repairs: usize) -> Self {
+        self.max_repairs = Some(max_repairs);
+        self
+    }
+
+    /// Maximum number of tokens read from one input
+    /// The rest of the input is ignored; `try_autocorrect` fails instead
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Maximum length in bytes of a decoded string
+    /// Longer strings are cut at the limit; `try_autocorrect` fails instead
+    pub fn max_string_length(mut self, max_string_length: usize) -> Self {
+        self.max_string_length = Some(max_string_length);
+        self
+    }
+
+    /// Action configured for a kind of repair
+    #[inline]
+    pub fn action(&self, kind: RepairKind) -> &RepairAction {
+        self.actions.get(&kind).unwrap_or(&self.default_action)
+    }
+
+    #[inline]
+    pub fn sorts_keys(&self) -> bool {
+        self.sort_keys
+    }
+
+    #[inline]
+    pub fn extracts_embedded(&self) -> bool {
+        self.extract_embedded
+    }
+
+    #[inline]
+    pub fn json_schema(&self) -> Option<&Schema> {
+        self.schema.as_deref()
+    }
+
+    #[inline]
+    pub fn non_finite_policy(&self) -> NonFiniteNumbers {
+        self.non_finite
+    }
+
+    #[inline]
+    pub fn duplicate_key_policy(&self) -> DuplicateKeys {
+        self.duplicate_keys
+    }
+
+    #[inline]
+    pub fn array_pair_policy(&self) -> ArrayPairs {
+        self.array_pairs
+    }
+
+    #[inline]
+    pub fn depth_limit(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    #[inline]
+    pub fn input_size_limit(&self) -> Option<usize> {
+        self.max_input_size
+    }
+
+    #[inline]
+    pub fn repair_limit(&self) -> Option<usize> {
+        self.max_repairs
+    }
+
+    #[inline]
+    pub fn token_limit(&self) -> Option<usize> {
+        self.max_tokens
+    }
+
+    #[inline]
+    pub fn string_length_limit(&self) -> Option<usize> {
+        self.max_string_length
+    }
+}
+
+impl Default for CorrectorOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_repairs_everything() {
+        let options = CorrectorOptions::new();
+        assert_eq!(options.action(RepairKind::CompletedLiteral), &RepairAction::Repair);
+        assert_eq!(options.action(RepairKind::InsertedCloser), &RepairAction::Repair);
+        assert!(!options.sorts_keys());
+        assert_eq!(options.depth_limit(), Some(DEFAULT_MAX_DEPTH));
+        assert_eq!(options.input_size_limit(), None);
+    }
+
+    #[test]
+    fn test_strict_with_overrides() {
+        let options = CorrectorOptions::strict()
+            .partial_literals(RepairAction::Repair)
+            .empty_fallback(RepairAction::Substitute(Value::Null));
+
+        assert_eq!(options.action(RepairKind::InsertedComma), &RepairAction::Error);
+        assert_eq!(options.action(RepairKind::CompletedLiteral), &RepairAction::Repair);
+        assert_eq!(
+            options.action(RepairKind::EmptyFallback),
+            &RepairAction::Substitute(Value::Null)
+        );
+    }
+}
@@ -1,6 +1,168 @@
-use crate::token::{Token, TokenKind};
+use crate::duplicates;
+use crate::events::push_escaped;
+use crate::number;
+use crate::options::{ArrayPairs, CorrectorOptions, DuplicateKeys, RepairAction};
+use crate::report::{Repair, RepairKind};
+use crate::schema::{JsonType, Schema, SchemaRef};
+use crate::token::{Span, Token, TokenKind};
 use serde_json::{Map, Value};
 
+/// Receives what the parser finds as it goes, each value addressed by JSON pointer
+/// Nothing inside the value of a repeated key is reported before that value is stored
+pub(crate) trait ParseSink {
+    /// False for a sink that ignores everything, so no pointers are built for it
+    const ACTIVE: bool = true;
+
+    /// A member or element began: its key was read or its first token seen
+    fn started(&mut self, path: String);
+
+    /// A value was stored; `streamed` marks a container whose contents were reported
+    /// before it
+    fn completed(&mut self, path: String, value: &Value, streamed: bool);
+
+    /// A repeated key left `value` in the object, by the duplicate key policy
+    fn replaced(&mut self, path: String, value: &Value);
+}
+
+/// Batch parsing reports nothing
+impl ParseSink for () {
+    const ACTIVE: bool = false;
+
+    fn started(&mut self, _: String) {}
+
+    fn completed(&mut self, _: String, _: &Value, _: bool) {}
+
+    fn replaced(&mut self, _: String, _: &Value) {}
+}
+
+/// A `key: value` pair found inside an array, stored there as a one-member object
+struct Pair {
+    /// Position of the object among the array's elements
+    index: usize,
+    span: Span,
+    /// The pair as written, in compact form
+    original: String,
+}
+
+/// What an object expects next; each variant but `Key` holds the index of the key token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Member {
+    /// A key, a separating comma or the closer
+    Key,
+    /// The `:` after the key
+    Colon(usize),
+    /// The value, or the end of the container holding it
+    Value(usize),
+    /// The token after garbage in place of the value, where the missing value goes
+    Garbage(usize),
+    /// The token after a key an object further out declares, which ends this object if it
+    /// is a `:`
+    Held(usize),
+}
+
+/// What an array expects next; each variant but `Next` holds the index of a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Element {
+    /// An element, a separating comma or the closer
+    Next,
+    /// The token after a scalar element, which makes it the key of a pair if it is a `:`
+    Held(usize),
+    /// The value of the pair with this key, or the end of the container holding it
+    Pair(usize),
+    /// The token after garbage in place of the value of the pair with this key
+    PairGarbage(usize),
+}
+
+/// An object whose closer has not been reached
+struct Object {
+    map: Map<String, Value>,
+    /// Keys whose value collects repeats, for `DuplicateKeys::Collect`
+    collected: Vec<String>,
+    member: Member,
+    expect_comma: bool,
+    /// The separating comma just passed, which is trailing if the closer comes next
+    comma: Option<usize>,
+    /// Index of the object's schema; an object with one takes back the keys it declares
+    /// from the containers inside it
+    schema: Option<usize>,
+    /// Containers closed early for this object whose closers are still ahead
+    leftover: usize,
+    /// The current key is already in the object, so its value is not reported as it arrives
+    repeat: bool,
+}
+
+/// An array whose closer has not been reached
+struct Array {
+    items: Vec<Value>,
+    pairs: Vec<Pair>,
+    element: Element,
+    expect_comma: bool,
+    /// Garbage took the place of an element, so a comma after it is a separator
+    garbage: bool,
+    /// The comma just passed, which is trailing if the closer comes next
+    comma: Option<usize>,
+    /// Index of the schema of the elements
+    schema: Option<usize>,
+}
+
+enum Body {
+    Object(Object),
+    Array(Array),
+    /// A container past the depth limit, replaced with `null` without looking inside, and
+    /// how many containers are open in it
+    Skipped(usize),
+}
+
+/// A container whose closer has not been reached
+struct Frame {
+    /// Index of the opener
+    start: usize,
+    /// Index of the schema of the slot the container fills, which the finished container
+    /// is brought in line with
+    slot: Option<usize>,
+    /// Frame of the innermost object with a schema around the container, unless something
+    /// but arrays lies in between; the container hands back the keys that object declares
+    outer: Option<usize>,
+    body: Body,
+}
+
+/// How far parsing got: the containers open before the next token and what each expects
+/// The parser takes one token at a time and never looks ahead, so it can stop wherever the
+/// tokens run out and carry on once more arrive
+pub(crate) struct ParseState {
+    frames: Vec<Frame>,
+    idx: usize,
+    /// The top-level value, once it is complete
+    result: Option<ParseResult>,
+}
+
+impl ParseState {
+    /// Start parsing a value at the token at `idx`
+    pub(crate) fn new(idx: usize) -> Self {
+        Self {
+            frames: Vec::new(),
+            idx,
+            result: None,
+        }
+    }
+
+    /// The innermost container, if it is an object
+    fn object(&mut self) -> Option<&mut Object> {
+        match self.frames.last_mut().map(|frame| &mut frame.body) {
+            Some(Body::Object(object)) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// The innermost container, if it is an array
+    fn array(&mut self) -> Option<&mut Array> {
+        match self.frames.last_mut().map(|frame| &mut frame.body) {
+            Some(Body::Array(array)) => Some(array),
+            _ => None,
+        }
+    }
+}
+
 /// Result of parsing operation
 #[derive(Debug)]
 pub struct ParseResult {
@@ -9,231 +171,1088 @@ pub struct ParseResult {
 }
 
 /// High-performance JSON parser optimized for memory efficiency
-pub struct JSONParser;
+/// Nesting is kept on the heap, so deep input cannot overflow the stack while parsing
+pub struct JSONParser {
+    options: CorrectorOptions,
+}
 
 impl JSONParser {
     pub fn new() -> Self {
-        Self {}
+        Self::with_options(CorrectorOptions::default())
+    }
+
+    /// Create a parser that applies the given repair policy to objects and arrays
+    pub fn with_options(options: CorrectorOptions) -> Self {
+        Self { options }
     }
 
     /// Parse tokens into JSON value
     pub fn parse(&self, token_list: &[Token], idx: usize) -> ParseResult {
-        if idx >= token_list.len() {
-            return ParseResult {
-                value: None,
-                index: idx,
-            };
+        self.parse_with_repairs(token_list, idx, &mut Vec::new())
+    }
+
+    /// Parse tokens into JSON value, recording every repair made along the way
+    pub fn parse_with_repairs(
+        &self,
+        token_list: &[Token],
+        idx: usize,
+        repairs: &mut Vec<Repair>,
+    ) -> ParseResult {
+        let mut state = ParseState::new(idx);
+        self.finish(&mut state, token_list, repairs, &mut ())
+    }
+
+    /// Parse on as far as the tokens go, which may end anywhere inside the value
+    pub(crate) fn advance<S: ParseSink>(
+        &self,
+        state: &mut ParseState,
+        token_list: &[Token],
+        repairs: &mut Vec<Repair>,
+        sink: &mut S,
+    ) {
+        self.start(state, token_list, repairs, sink).advance();
+    }
+
+    /// Parse the rest of the tokens, then close whatever is still open where they end
+    pub(crate) fn finish<S: ParseSink>(
+        &self,
+        state: &mut ParseState,
+        token_list: &[Token],
+        repairs: &mut Vec<Repair>,
+        sink: &mut S,
+    ) -> ParseResult {
+        let mut parse = self.start(state, token_list, repairs, sink);
+        parse.advance();
+        loop {
+            if let Some(result) = parse.state.result.take() {
+                return result;
+            }
+            parse.close_at_end();
         }
+    }
 
-        let token = &token_list[idx];
-        match token.kind {
-            TokenKind::LBrace => self.parse_object(token_list, idx + 1),
-            TokenKind::LBracket => self.parse_array(token_list, idx + 1),
-            TokenKind::String => ParseResult {
-                value: Some(Value::String(
-                    token.value.as_ref().map(|s| s.clone()).unwrap_or_default(),
-                )),
-                index: idx + 1,
-            },
-            TokenKind::Number => self.parse_number(token, idx),
-            TokenKind::True => ParseResult {
-                value: Some(Value::Bool(true)),
-                index: idx + 1,
-            },
-            TokenKind::False => ParseResult {
-                value: Some(Value::Bool(false)),
-                index: idx + 1,
-            },
-            TokenKind::Null => ParseResult {
-                value: Some(Value::Null),
-                index: idx + 1,
+    /// Pointer of the slot a value arriving next would go into, unless a value has no place
+    /// there or would not be reported, as inside the value of a repeated key
+    pub(crate) fn value_path(&self, state: &ParseState, token_list: &[Token]) -> Option<String> {
+        if state.result.is_some() || muted(&state.frames) {
+            return None;
+        }
+        let open = match state.frames.last().map(|frame| &frame.body) {
+            None => true,
+            Some(Body::Object(object)) => matches!(object.member, Member::Value(_)),
+            Some(Body::Array(array)) => matches!(array.element, Element::Next | Element::Pair(_)),
+            Some(Body::Skipped(_)) => false,
+        };
+        let policy = self.options.duplicate_key_policy();
+        open.then(|| pointer(&state.frames, token_list, policy))
+    }
+
+    fn start<'a, S: ParseSink>(
+        &'a self,
+        state: &'a mut ParseState,
+        tokens: &'a [Token],
+        repairs: &'a mut Vec<Repair>,
+        sink: &'a mut S,
+    ) -> Parse<'a, S> {
+        Parse {
+            options: &self.options,
+            schema: self.options.json_schema(),
+            tokens,
+            state,
+            repairs,
+            sink,
+        }
+    }
+}
+
+impl Default for JSONParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One go of the parser over the tokens there are so far
+struct Parse<'a, S> {
+    options: &'a CorrectorOptions,
+    schema: Option<&'a Schema>,
+    tokens: &'a [Token],
+    state: &'a mut ParseState,
+    repairs: &'a mut Vec<Repair>,
+    sink: &'a mut S,
+}
+
+impl<'a, S: ParseSink> Parse<'a, S> {
+    /// Take tokens until they run out or the top-level value is complete
+    fn advance(&mut self) {
+        while self.state.result.is_none() && self.state.idx < self.tokens.len() {
+            match self.state.frames.last().map(|frame| &frame.body) {
+                None => self.root_token(),
+                Some(Body::Object(_)) => self.object_token(),
+                Some(Body::Array(_)) => self.array_token(),
+                Some(Body::Skipped(_)) => self.skipped_token(),
+            }
+        }
+    }
+
+    /// The token where the top-level value belongs
+    fn root_token(&mut self) {
+        let idx = self.state.idx;
+        let token = &self.tokens[idx];
+        if starts_value(token.kind) || ends_slot(token.kind) {
+            return self.value(self.schema.map(Schema::root), None);
+        }
+        // A dropped literal was already reported by the tokenizer
+        if token.kind != TokenKind::Dropped {
+            Self::drop_token(token, self.repairs);
+        }
+        self.state.idx = idx + 1;
+        self.state.result = Some(ParseResult {
+            value: None,
+            index: idx + 1,
+        });
+    }
+
+    /// Start the value at the current token, or leave its slot empty if the token ends it
+    /// `slot` is the schema of the value and `outer` the object a container there hands keys
+    /// back to
+    fn value(&mut self, slot: Option<SchemaRef<'a>>, outer: Option<usize>) {
+        let tokens = self.tokens;
+        let idx = self.state.idx;
+        let token = &tokens[idx];
+        if ends_slot(token.kind) {
+            return self.deliver(None, idx, false);
+        }
+        self.start();
+        let depth = self.state.frames.len();
+        let body = match token.kind {
+            TokenKind::LBrace | TokenKind::LBracket
+                if self.options.depth_limit().is_some_and(|max| depth >= max) =>
+            {
+                Body::Skipped(1)
+            }
+            TokenKind::LBrace => {
+                let schema = slot.map(|s| s.container(JsonType::Object).index());
+                Body::Object(Object {
+                    map: Map::with_capacity(16), // Pre-allocate for typical object size
+                    collected: Vec::new(),
+                    member: Member::Key,
+                    expect_comma: false,
+                    comma: None,
+                    schema,
+                    leftover: 0,
+                    repeat: false,
+                })
+            }
+            TokenKind::LBracket => {
+                let items = slot.and_then(|s| s.container(JsonType::Array).items());
+                Body::Array(Array {
+                    items: Vec::with_capacity(16), // Pre-allocate for typical array size
+                    pairs: Vec::new(),
+                    element: Element::Next,
+                    expect_comma: false,
+                    garbage: false,
+                    comma: None,
+                    schema: items.map(SchemaRef::index),
+                })
+            }
+            _ => {
+                let value = self.read_scalar(token);
+                let value = self.conform(slot, idx, value);
+                self.state.idx = idx + 1;
+                return self.deliver(Some(value), idx + 1, false);
+            }
+        };
+        self.state.idx = idx + 1;
+        self.state.frames.push(Frame {
+            start: idx,
+            slot: slot.map(SchemaRef::index),
+            outer,
+            body,
+        });
+    }
+
+    /// Value of a scalar token, reporting a number that could only be kept as a string
+    fn read_scalar(&mut self, token: &Token) -> Value {
+        let value = scalar(token).unwrap_or_default();
+        // Fallback to string if parsing fails
+        if let (TokenKind::Number, Value::String(text)) = (token.kind, &value) {
+            let replacement = format!("\"{}\"", text);
+            let repair = Repair::new(RepairKind::NumberToString, token.span, text, replacement);
+            self.repairs.push(repair);
+        }
+        value
+    }
+
+    /// Put a finished value, or none, in the slot the innermost container is at
+    /// `index` is the token after the value; `streamed` marks a container
+    fn deliver(&mut self, value: Option<Value>, index: usize, streamed: bool) {
+        let Some(frame) = self.state.frames.last() else {
+            if let (true, Some(value)) = (S::ACTIVE, &value) {
+                self.sink.completed(String::new(), value, streamed);
+            }
+            self.state.result = Some(ParseResult { value, index });
+            return;
+        };
+        match &frame.body {
+            Body::Object(object) => {
+                if let Member::Value(key) | Member::Garbage(key) = object.member {
+                    self.member(key, value, index, streamed);
+                }
+            }
+            Body::Array(array) => match array.element {
+                Element::Pair(key) | Element::PairGarbage(key) => {
+                    self.pair(key, value, index, streamed)
+                }
+                _ => {
+                    if let Some(value) = value {
+                        self.element(value, streamed);
+                    }
+                }
             },
-            TokenKind::RBrace | TokenKind::RBracket | TokenKind::Eof => ParseResult {
-                value: None,
-                index: idx,
+            Body::Skipped(_) => {}
+        }
+    }
+
+    /// Close the innermost container, which ends before `index`, and put it in its slot
+    /// `closer` is the token that closed an array, which reports a pair in it
+    fn close(&mut self, index: usize, closer: Option<&Token>) {
+        let Some(frame) = self.state.frames.pop() else {
+            return;
+        };
+        let mut streamed = true;
+        let value = match frame.body {
+            Body::Object(object) => Value::Object(object.map),
+            Body::Array(array) => self.finish_array(frame.start, array.items, array.pairs, closer),
+            // The depth limit replaced the container without descending into it
+            Body::Skipped(_) => {
+                let span = Span {
+                    end: self.tokens[index - 1].span.end,
+                    ..self.tokens[frame.start].span
+                };
+                self.repairs.push(Repair::new(RepairKind::DepthLimited, span, "", "null"));
+                streamed = false;
+                Value::Null
+            }
+        };
+        let value = self.conform(self.node(frame.slot), frame.start, value);
+        self.state.idx = index;
+        self.deliver(Some(value), index, streamed);
+    }
+
+    /// Close the innermost container where the tokens end, as if the input stopped there
+    fn close_at_end(&mut self) {
+        let index = self.state.idx;
+        let Some(frame) = self.state.frames.last() else {
+            self.state.result = Some(ParseResult { value: None, index });
+            return;
+        };
+        match &frame.body {
+            Body::Object(object) => match object.member {
+                Member::Key => {
+                    if let Some(last) = self.tokens.last() {
+                        self.insert_defaults(last);
+                    }
+                    self.close(index, None);
+                }
+                Member::Colon(key) => self.missing_colon(key),
+                Member::Value(key) | Member::Garbage(key) => self.member(key, None, index, false),
+                Member::Held(key) => {
+                    self.read_key(key);
+                    self.missing_colon(key);
+                }
             },
-            _ => ParseResult {
-                value: None,
-                index: idx + 1,
+            Body::Array(array) => match array.element {
+                Element::Next => self.close(index, None),
+                Element::Held(held) => self.held_element(held),
+                Element::Pair(key) | Element::PairGarbage(key) => {
+                    self.pair(key, None, index, false)
+                }
             },
+            Body::Skipped(_) => self.close(index, None),
         }
     }
 
-    /// Parse number token with optimized conversion
-    #[inline]
-    fn parse_number(&self, token: &Token, idx: usize) -> ParseResult {
-        let value_str = token.value.as_ref().map(|s| s.as_str()).unwrap_or("0");
-
-        if value_str.contains('.') || value_str.contains('e') || value_str.contains('E') {
-            // Floating point number
-            if let Ok(num) = value_str.parse::<f64>() {
-                return ParseResult {
-                    value: Some(Value::Number(
-                        serde_json::Number::from_f64(num)
-                            .unwrap_or_else(|| serde_json::Number::from(0)),
-                    )),
-                    index: idx + 1,
-                };
+    /// The next token inside an object
+    fn object_token(&mut self) {
+        let tokens = self.tokens;
+        let idx = self.state.idx;
+        let token = &tokens[idx];
+        let here = self.state.frames.len() - 1;
+        let outer = self.state.frames[here].outer;
+        let Some(object) = self.state.object() else {
+            return;
+        };
+        if let Some(comma) = object.comma.take() {
+            Self::check_trailing_comma(tokens, comma, TokenKind::RBrace, self.repairs);
+        }
+        let schema = object.schema;
+
+        match object.member {
+            Member::Key => {}
+            Member::Colon(key) => {
+                if token.kind == TokenKind::Colon {
+                    object.member = Member::Value(key);
+                    self.state.idx += 1;
+                } else {
+                    self.missing_colon(key);
+                }
+                return;
             }
-        } else {
-            // Integer number
-            if let Ok(num) = value_str.parse::<i64>() {
-                return ParseResult {
-                    value: Some(Value::Number(serde_json::Number::from(num))),
-                    index: idx + 1,
-                };
+            Member::Value(key) => {
+                // The policy removed the value, so the whole entry goes
+                if token.kind == TokenKind::Dropped {
+                    object.member = Member::Key;
+                    object.expect_comma = true;
+                    object.repeat = false;
+                    self.state.idx += 1;
+                } else if starts_value(token.kind) || ends_slot(token.kind) {
+                    let property = self.property(schema, key);
+                    self.value(property, schema.map(|_| here));
+                } else {
+                    object.member = Member::Garbage(key);
+                    Self::drop_token(token, self.repairs);
+                    self.state.idx += 1;
+                }
+                return;
+            }
+            Member::Garbage(key) => return self.member(key, None, idx, false),
+            Member::Held(key) => {
+                if token.kind == TokenKind::Colon {
+                    return self.hand_back(key);
+                }
+                self.read_key(key);
+                return self.missing_colon(key);
             }
         }
 
-        // Fallback to string if parsing fails
-        ParseResult {
-            value: Some(Value::String(value_str.to_string())),
-            index: idx + 1,
+        // The closer of a container that handed a key back to this object
+        if object.leftover > 0 && matches!(token.kind, TokenKind::RBrace | TokenKind::RBracket) {
+            object.leftover -= 1;
+            Self::drop_closer(token, self.repairs);
+            self.state.idx += 1;
+            return;
         }
+
+        // A member of an object around this one ends this one first
+        if token.kind == TokenKind::String {
+            let key = token.value.as_deref().unwrap_or_default();
+            let rejected = self.node(schema).is_some_and(|s| s.rejects_key(key));
+            if rejected && self.declaring(outer, key).is_some() {
+                if let Some(object) = self.state.object() {
+                    object.member = Member::Held(idx);
+                }
+                self.state.idx += 1;
+                return;
+            }
+        }
+
+        // Check for object end
+        if token.kind == TokenKind::RBrace || token.kind == TokenKind::Eof {
+            self.insert_defaults(token);
+            return self.close(idx + 1, Some(token));
+        }
+
+        let Some(object) = self.state.object() else {
+            return;
+        };
+        // Handle comma expectation
+        if object.expect_comma {
+            if token.kind == TokenKind::Comma {
+                object.expect_comma = false;
+                object.comma = Some(idx);
+                self.state.idx += 1;
+                return;
+            }
+            // A missing comma before a key is inserted by `read_key`
+            if token.kind != TokenKind::String {
+                object.expect_comma = false;
+            }
+        }
+
+        match token.kind {
+            TokenKind::String => self.read_key(idx),
+            // Already reported by the tokenizer
+            TokenKind::Dropped => {}
+            // Unexpected token - skip it
+            _ => Self::drop_token(token, self.repairs),
+        }
+        self.state.idx += 1;
     }
 
-    /// Parse JSON object with optimized memory allocation
-    fn parse_object(&self, token_list: &[Token], start_idx: usize) -> ParseResult {
-        let mut result = Map::with_capacity(16); // Pre-allocate for typical object size
-        let mut expect_comma = false;
-        let size = token_list.len();
-        let mut idx = start_idx;
+    /// Take the string at `key` as the next key of the innermost object
+    fn read_key(&mut self, key: usize) {
+        let token = &self.tokens[key];
+        let policy = self.options.duplicate_key_policy();
+        let Some(object) = self.state.object() else {
+            return;
+        };
+        if object.expect_comma {
+            // Missing comma - continue anyway (auto-fix)
+            let at = token.span.empty();
+            self.repairs.push(Repair::new(RepairKind::InsertedComma, at, "", ","));
+            object.expect_comma = false;
+        }
+        let name = token.value.as_deref().unwrap_or_default();
+        object.repeat = policy != DuplicateKeys::Rename && object.map.contains_key(name);
+        object.member = Member::Colon(key);
+        self.start();
+    }
 
-        while idx < size {
-            let token = &token_list[idx];
+    /// The key at `key` has no `:` after it, so it gets a value by the policy
+    fn missing_colon(&mut self, key: usize) {
+        let tokens = self.tokens;
+        let token = &tokens[key];
+        let key_span = token.span;
+        let at = tokens.get(key + 1).map_or(key_span, |t| t.span).empty();
+        let schema = self.state.object().and_then(|object| object.schema);
+        let default = self.property(schema, key).and_then(SchemaRef::default_value);
 
-            // Check for object end
-            if token.kind == TokenKind::RBrace || token.kind == TokenKind::Eof {
-                return ParseResult {
-                    value: Some(Value::Object(result)),
-                    index: idx + 1,
-                };
+        let kind = RepairKind::MissingColon;
+        let value = match self.options.action(kind) {
+            RepairAction::Drop => {
+                self.repairs.push(Repair::new(kind, key_span, token.text(), ""));
+                None
+            }
+            RepairAction::Substitute(value) => Some(value.clone()),
+            RepairAction::Repair | RepairAction::Error => {
+                Some(default.cloned().unwrap_or(Value::Null))
             }
+        };
+        if let Some(value) = value {
+            self.repairs.push(Repair::new(kind, at, "", format!(":{}", value)));
+            let name = token.value.clone().unwrap_or_default();
+            self.store(name, value, key_span, false);
+        }
+        if let Some(object) = self.state.object() {
+            object.member = Member::Key;
+            object.expect_comma = true;
+            object.repeat = false;
+        }
+    }
 
-            // Handle comma expectation
-            if expect_comma {
-                if token.kind == TokenKind::Comma {
-                    idx += 1;
-                    expect_comma = false;
-                    continue;
-                } else if token.kind == TokenKind::RBrace {
-                    return ParseResult {
-                        value: Some(Value::Object(result)),
-                        index: idx + 1,
-                    };
+    /// Store the member whose key is at `key` and whose value ends before `index`, filling
+    /// in a missing value
+    fn member(&mut self, key: usize, value: Option<Value>, index: usize, streamed: bool) {
+        let tokens = self.tokens;
+        let token = &tokens[key];
+        let key_span = token.span;
+        // The member runs from the key to the last token of its value
+        let last = tokens.get(index.saturating_sub(1));
+        let end = last.map_or(key_span.end, |t| t.span.end).max(key_span.end);
+        let span = Span { end, ..key_span };
+        let schema = self.state.object().and_then(|object| object.schema);
+
+        let value = match (value, self.property(schema, key).and_then(SchemaRef::default_value)) {
+            (Some(value), _) => Some(value),
+            (None, Some(value)) => {
+                let at = tokens.get(index).map_or(key_span, |t| t.span).empty();
+                let kind = RepairKind::InsertedDefault;
+                self.repairs.push(Repair::new(kind, at, "", value.to_string()));
+                Some(value.clone())
+            }
+            (None, None) => {
+                let at = tokens.get(index).map_or(key_span, |t| t.span);
+                let original = format!("{}:", token.text());
+                self.missing_value(at, key_span, original)
+            }
+        };
+        if let Some(value) = value {
+            self.store(token.value.clone().unwrap_or_default(), value, span, streamed);
+        }
+        if let Some(object) = self.state.object() {
+            object.member = Member::Key;
+            object.expect_comma = true;
+            object.repeat = false;
+        }
+    }
+
+    /// Store a member of the innermost object and report it
+    fn store(&mut self, key: String, value: Value, span: Span, streamed: bool) {
+        let policy = self.options.duplicate_key_policy();
+        if let Some(object) = self.state.object() {
+            object.repeat = false;
+        }
+        let report = S::ACTIVE && !muted(&self.state.frames);
+        let path = if report { self.path() } else { String::new() };
+        let Some(object) = self.state.object() else {
+            return;
+        };
+        let repeated = object.map.contains_key(&key);
+        if report && (!repeated || policy == DuplicateKeys::Rename) {
+            self.sink.completed(path.clone(), &value, streamed);
+        }
+        let replaced = (report && repeated).then(|| key.clone());
+        let (map, collected) = (&mut object.map, &mut object.collected);
+        Self::insert_member(policy, map, collected, (key, value), span, self.repairs);
+        if let Some(key) = replaced {
+            if !matches!(policy, DuplicateKeys::KeepFirst | DuplicateKeys::Rename) {
+                if let Some(value) = object.map.get(&key) {
+                    self.sink.replaced(path, value);
                 }
-                // Missing comma - continue anyway (auto-fix)
-                expect_comma = false;
-            }
-
-            // Parse key-value pair
-            if token.kind == TokenKind::String {
-                let key = token.value.as_ref().map(|s| s.clone()).unwrap_or_default();
-                idx += 1;
-
-                // Expect colon
-                if idx < size && token_list[idx].kind == TokenKind::Colon {
-                    idx += 1;
-                    
-                    // Parse value
-                    let pr = self.parse(token_list, idx);
-                    if let Some(value) = pr.value {
-                        result.insert(key, value);
-                    }
-                    idx = pr.index;
-                    expect_comma = true;
+            }
+        }
+    }
+
+    /// The next token inside an array
+    fn array_token(&mut self) {
+        let tokens = self.tokens;
+        let idx = self.state.idx;
+        let token = &tokens[idx];
+        let outer = self.state.frames.last().and_then(|frame| frame.outer);
+        let Some(array) = self.state.array() else {
+            return;
+        };
+        if let Some(comma) = array.comma.take() {
+            Self::check_trailing_comma(tokens, comma, TokenKind::RBracket, self.repairs);
+        }
+
+        match array.element {
+            Element::Next => {}
+            Element::Held(held) if token.kind == TokenKind::Colon => {
+                let key = &tokens[held];
+                let name = key.value.as_deref().unwrap_or_default();
+                // A member of an object around the array ends the array there
+                if key.kind == TokenKind::String && self.declaring(outer, name).is_some() {
+                    return self.hand_back(held);
+                }
+                // A scalar followed by `:` is an object member written inside the array
+                self.next_element(held);
+                if let Some(array) = self.state.array() {
+                    array.element = Element::Pair(held);
+                }
+                self.state.idx += 1;
+                return;
+            }
+            Element::Held(held) => return self.held_element(held),
+            Element::Pair(key) => {
+                // The policy removed the value, so the whole pair goes
+                if token.kind == TokenKind::Dropped {
+                    array.element = Element::Next;
+                    self.state.idx += 1;
+                } else if starts_value(token.kind) || ends_slot(token.kind) {
+                    self.value(None, None);
                 } else {
-                    // Missing colon - treat key as standalone value with null
-                    result.insert(key, Value::Null);
-                    expect_comma = true;
+                    array.element = Element::PairGarbage(key);
+                    Self::drop_token(token, self.repairs);
+                    self.state.idx += 1;
+                }
+                return;
+            }
+            Element::PairGarbage(key) => return self.pair(key, None, idx, false),
+        }
+
+        // Check for array end
+        if token.kind == TokenKind::RBracket || token.kind == TokenKind::Eof {
+            return self.close(idx + 1, Some(token));
+        }
+
+        // Handle comma expectation
+        if (array.expect_comma || array.garbage) && token.kind == TokenKind::Comma {
+            array.expect_comma = false;
+            array.garbage = false;
+            array.comma = Some(idx);
+            self.state.idx += 1;
+            return;
+        }
+
+        // A comma where an element belongs leaves an empty slot, as in `[1,,2]` or `[,1]`
+        if token.kind == TokenKind::Comma {
+            let original = ",".to_string();
+            if let Some(value) = self.missing_value(token.span, token.span, original) {
+                self.start();
+                self.element(value, false);
+                if let Some(array) = self.state.array() {
+                    array.comma = Some(idx);
                 }
-            } else {
-                // Unexpected token - skip it
-                idx += 1;
             }
+            self.state.idx += 1;
+            return;
+        }
+
+        // The policy removed this element; its separator may still follow
+        if token.kind == TokenKind::Dropped {
+            array.expect_comma = true;
+            self.state.idx += 1;
+            return;
+        }
+
+        // Unexpected token - skip it and carry on with the next element
+        if !starts_value(token.kind) {
+            array.garbage = true;
+            Self::drop_token(token, self.repairs);
+            self.state.idx += 1;
+            return;
         }
 
-        ParseResult {
-            value: Some(Value::Object(result)),
-            index: idx,
+        // A scalar waits for the next token, which may make it the key of a pair
+        if !matches!(token.kind, TokenKind::LBrace | TokenKind::LBracket) {
+            array.element = Element::Held(idx);
+            self.start();
+            self.state.idx += 1;
+            return;
         }
+
+        let items = array.schema;
+        self.next_element(idx);
+        self.value(self.node(items), outer);
     }
 
-    /// Parse JSON array with optimized memory allocation
-    fn parse_array(&self, token_list: &[Token], start_idx: usize) -> ParseResult {
-        let mut result = Vec::with_capacity(16); // Pre-allocate for typical array size
-        let mut expect_comma = false;
-        let size = token_list.len();
-        let mut idx = start_idx;
+    /// An element starts at `idx`, after a comma or with one missing
+    fn next_element(&mut self, idx: usize) {
+        let Some(array) = self.state.array() else {
+            return;
+        };
+        if array.expect_comma {
+            // Missing comma - continue anyway (auto-fix)
+            let at = self.tokens[idx].span.empty();
+            self.repairs.push(Repair::new(RepairKind::InsertedComma, at, "", ","));
+        }
+        array.expect_comma = true;
+    }
 
-        while idx < size {
-            let token = &token_list[idx];
+    /// The scalar at `held` is an element of the innermost array, not the key of a pair
+    fn held_element(&mut self, held: usize) {
+        self.next_element(held);
+        let Some(array) = self.state.array() else {
+            return;
+        };
+        array.element = Element::Next;
+        let items = array.schema;
+        let tokens = self.tokens;
+        let value = self.read_scalar(&tokens[held]);
+        let value = self.conform(self.node(items), held, value);
+        self.element(value, false);
+    }
 
-            // Check for array end
-            if token.kind == TokenKind::RBracket || token.kind == TokenKind::Eof {
-                return ParseResult {
-                    value: Some(Value::Array(result)),
-                    index: idx + 1,
-                };
+    /// Append an element to the innermost array and report it
+    fn element(&mut self, value: Value, streamed: bool) {
+        if S::ACTIVE && !muted(&self.state.frames) {
+            let path = self.path();
+            self.sink.completed(path, &value, streamed);
+        }
+        if let Some(array) = self.state.array() {
+            array.items.push(value);
+        }
+    }
+
+    /// Store the pair whose key is at `key` and whose value ends before `index` in the
+    /// innermost array, as a one-member object
+    fn pair(&mut self, key: usize, value: Option<Value>, index: usize, streamed: bool) {
+        let tokens = self.tokens;
+        let key_token = &tokens[key];
+        let last = tokens.get(index.saturating_sub(1));
+        let end = last.map_or(key_token.span.end, |t| t.span.end).max(key_token.span.end);
+        let span = Span {
+            end,
+            ..key_token.span
+        };
+        let value = match value {
+            Some(value) => value,
+            None => {
+                let at = tokens.get(index).map_or(span, |t| t.span);
+                let original = format!("{}:", key_token.text());
+                match self.missing_value(at, key_token.span, original) {
+                    Some(value) => {
+                        self.start();
+                        value
+                    }
+                    None => {
+                        if let Some(array) = self.state.array() {
+                            array.element = Element::Next;
+                        }
+                        return;
+                    }
+                }
             }
+        };
 
-            // Handle comma expectation
-            if expect_comma {
-                if token.kind == TokenKind::Comma {
-                    idx += 1;
-                    expect_comma = false;
-                    continue;
-                } else if token.kind == TokenKind::RBracket {
-                    return ParseResult {
-                        value: Some(Value::Array(result)),
-                        index: idx + 1,
-                    };
+        let report = S::ACTIVE && !muted(&self.state.frames);
+        if report {
+            let path = self.path();
+            self.sink.completed(path, &value, streamed);
+        }
+        let original = format!("{}:{}", key_token.text(), value);
+        let Some(array) = self.state.array() else {
+            return;
+        };
+        array.element = Element::Next;
+        array.pairs.push(Pair {
+            index: array.items.len(),
+            span,
+            original,
+        });
+        let mut member = Map::new();
+        member.insert(pair_key(key_token), value);
+        let member = Value::Object(member);
+        if report {
+            let path = self.path();
+            self.sink.completed(path, &member, true);
+        }
+        if let Some(array) = self.state.array() {
+            array.items.push(member);
+        }
+    }
+
+    /// The next token inside a container past the depth limit
+    fn skipped_token(&mut self) {
+        let idx = self.state.idx;
+        let Some(Frame {
+            body: Body::Skipped(open),
+            ..
+        }) = self.state.frames.last_mut()
+        else {
+            return;
+        };
+        match self.tokens[idx].kind {
+            TokenKind::LBrace | TokenKind::LBracket => *open += 1,
+            TokenKind::RBrace | TokenKind::RBracket => *open -= 1,
+            TokenKind::Eof => return self.close(idx, None),
+            _ => {}
+        }
+        self.state.idx = idx + 1;
+        if *open == 0 {
+            self.close(idx + 1, None);
+        }
+    }
+
+    /// Close the innermost container before the `key:` at `key`, which an object further out
+    /// declares. The closer is reported where it was missing; the one the container still
+    /// has ahead is skipped by that object, which picks up at the separating comma
+    fn hand_back(&mut self, key: usize) {
+        let tokens = self.tokens;
+        let Some(frame) = self.state.frames.last() else {
+            return;
+        };
+        let object = matches!(frame.body, Body::Object(_));
+        let name = tokens[key].value.as_deref().unwrap_or_default();
+        let Some(target) = self.declaring(frame.outer, name) else {
+            return;
+        };
+        if object {
+            self.insert_defaults(&tokens[key]);
+        }
+        let at = match key.checked_sub(1) {
+            Some(comma) if tokens[comma].kind == TokenKind::Comma => comma,
+            _ => key,
+        };
+        if let Some(Body::Object(target)) = self.state.frames.get_mut(target).map(|f| &mut f.body)
+        {
+            target.leftover += 1;
+        }
+        let text = if object { "}" } else { "]" };
+        let span = tokens[at].span.empty();
+        self.repairs.push(Repair::new(RepairKind::InsertedCloser, span, "", text));
+        self.close(at, None);
+    }
+
+    /// Frame of the nearest object, `outer` or one around it, that declares `key`, as long
+    /// as every object in between rejects the key
+    fn declaring(&self, mut outer: Option<usize>, key: &str) -> Option<usize> {
+        while let Some(index) = outer {
+            let frame = &self.state.frames[index];
+            let Body::Object(object) = &frame.body else {
+                return None;
+            };
+            let schema = self.node(object.schema)?;
+            if schema.declares(key) {
+                return Some(index);
+            }
+            if !schema.rejects_key(key) {
+                return None;
+            }
+            outer = frame.outer;
+        }
+        None
+    }
+
+    /// Schema node at `index`
+    fn node(&self, index: Option<usize>) -> Option<SchemaRef<'a>> {
+        Some(self.schema?.node(index?))
+    }
+
+    /// Schema of the member whose key is at `key`, in the object with the schema at `schema`
+    fn property(&self, schema: Option<usize>, key: usize) -> Option<SchemaRef<'a>> {
+        let key = self.tokens[key].value.as_deref().unwrap_or_default();
+        self.node(schema)?.property(key)
+    }
+
+    /// Report that a value begins in the slot the innermost container is at
+    fn start(&mut self) {
+        if S::ACTIVE && !muted(&self.state.frames) {
+            let path = self.path();
+            self.sink.started(path);
+        }
+    }
+
+    /// Pointer of the slot the innermost container is at
+    fn path(&self) -> String {
+        let policy = self.options.duplicate_key_policy();
+        pointer(&self.state.frames, self.tokens, policy)
+    }
+
+    /// Bring the value parsed from the token at `idx` in line with its schema
+    fn conform(&mut self, schema: Option<SchemaRef<'_>>, idx: usize, value: Value) -> Value {
+        let Some((conformed, kind)) = schema.and_then(|s| s.conform(&value)) else {
+            return value;
+        };
+
+        let token = &self.tokens[idx];
+        let repair = match (kind, token.kind) {
+            // Only the opening bracket is reported; the closer goes after the container
+            (RepairKind::WrappedInArray, TokenKind::LBrace | TokenKind::LBracket) => {
+                Repair::new(kind, token.span.empty(), "", "[")
+            }
+            _ => Repair::new(kind, token.span, token.text(), conformed.to_string()),
+        };
+        self.repairs.push(repair);
+        conformed
+    }
+
+    /// Add the defaults of required properties the innermost object is missing, before
+    /// `closer`
+    fn insert_defaults(&mut self, closer: &Token) {
+        let Some(object) = self.state.frames.last().and_then(|frame| match &frame.body {
+            Body::Object(object) => Some(object),
+            _ => None,
+        }) else {
+            return;
+        };
+        let Some(schema) = self.node(object.schema) else {
+            return;
+        };
+        let missing: Vec<(String, Value)> = schema
+            .missing_defaults(&object.map)
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        let report = S::ACTIVE && !muted(&self.state.frames);
+        let policy = self.options.duplicate_key_policy();
+        let inside = self.state.frames.len() - 1;
+        let path = pointer(&self.state.frames[..inside], self.tokens, policy);
+
+        for (key, value) in missing {
+            let Some(object) = self.state.object() else {
+                return;
+            };
+            let separator = if object.map.is_empty() { "" } else { "," };
+            let replacement = format!("{}{}:{}", separator, Value::from(key.as_str()), value);
+            let at = closer.span.empty();
+            self.repairs.push(Repair::new(RepairKind::InsertedDefault, at, "", replacement));
+            if report {
+                let mut member = format!("{}/", path);
+                push_escaped(&mut member, &key);
+                self.sink.completed(member, &value, false);
+            }
+            object.map.insert(key, value);
+        }
+    }
+
+    /// Value for an object key without a value or an empty array slot, `None` to drop it
+    /// `at` is where the value belongs; `Drop` reports removing `original` at `removed`
+    fn missing_value(&mut self, at: Span, removed: Span, original: String) -> Option<Value> {
+        let kind = RepairKind::MissingValue;
+        let value = match self.options.action(kind) {
+            RepairAction::Drop => {
+                self.repairs.push(Repair::new(kind, removed, original, ""));
+                return None;
+            }
+            RepairAction::Substitute(value) => value.clone(),
+            RepairAction::Repair | RepairAction::Error => Value::Null,
+        };
+        self.repairs.push(Repair::new(kind, at.empty(), "", value.to_string()));
+        Some(value)
+    }
+
+    /// Store a member, resolving a key the object already holds by the duplicate key policy
+    fn insert_member(
+        policy: DuplicateKeys,
+        object: &mut Map<String, Value>,
+        collected: &mut Vec<String>,
+        (key, value): (String, Value),
+        span: Span,
+        repairs: &mut Vec<Repair>,
+    ) {
+        if !object.contains_key(&key) {
+            object.insert(key, value);
+            return;
+        }
+        let original = duplicates::member(&key, &value);
+        let member = duplicates::resolve(policy, object, collected, key, value);
+        repairs.push(Repair::new(RepairKind::DuplicateKey, span, original, member));
+    }
+
+    /// Report the pairs found in the array opened at `start`, turning an array of nothing
+    /// but pairs into an object unless the policy wraps every pair
+    fn finish_array(
+        &mut self,
+        start: usize,
+        items: Vec<Value>,
+        pairs: Vec<Pair>,
+        closer: Option<&Token>,
+    ) -> Value {
+        if pairs.is_empty() {
+            return Value::Array(items);
+        }
+        let kind = RepairKind::PairInArray;
+        let policy = self.options.duplicate_key_policy();
+
+        if pairs.len() == items.len() && self.options.array_pair_policy() != ArrayPairs::Wrap {
+            self.repairs.push(Repair::new(kind, self.tokens[start].span, "[", "{"));
+            // A closer the token fixer inserted is already reported as `]`
+            if let Some(closer) = closer.filter(|t| t.kind == TokenKind::RBracket) {
+                if !closer.span.is_empty() {
+                    self.repairs.push(Repair::new(kind, closer.span, "]", "}"));
                 }
-                // Missing comma - continue anyway (auto-fix)
-            }
-            expect_comma = false;
-
-            // Check if token is a valid value
-            let valid_token = matches!(
-                token.kind,
-                TokenKind::LBrace
-                    | TokenKind::LBracket
-                    | TokenKind::String
-                    | TokenKind::Number
-                    | TokenKind::True
-                    | TokenKind::False
-                    | TokenKind::Null
-            );
-
-            if !valid_token {
-                return ParseResult {
-                    value: Some(Value::Array(result)),
-                    index: idx,
+            }
+            let mut object = Map::new();
+            let mut collected = Vec::new();
+            for (item, pair) in items.into_iter().zip(pairs) {
+                let Value::Object(member) = item else {
+                    continue;
                 };
+                for member in member {
+                    let (map, span) = (&mut object, pair.span);
+                    Self::insert_member(policy, map, &mut collected, member, span, self.repairs);
+                }
             }
+            return Value::Object(object);
+        }
 
-            // Parse array element
-            let pr = self.parse(token_list, idx);
-            if let Some(value) = pr.value {
-                result.push(value);
-            }
-            idx = pr.index;
-            expect_comma = true;
+        for pair in pairs {
+            let replacement = items[pair.index].to_string();
+            self.repairs.push(Repair::new(kind, pair.span, pair.original, replacement));
+        }
+        Value::Array(items)
+    }
+
+    /// Report a token that has no place where it stands
+    /// Closers count as dropped closers; stray ones were reported by the token fixer
+    fn drop_token(token: &Token, repairs: &mut Vec<Repair>) {
+        match token.kind {
+            TokenKind::RBrace | TokenKind::RBracket => Self::drop_closer(token, repairs),
+            TokenKind::Stray => {}
+            _ => repairs.push(Repair::new(RepairKind::DroppedToken, token.span, token.text(), "")),
+        }
+    }
+
+    /// Skip a closer that closes nothing here, such as that of a container closed early
+    /// A closer the token fixer inserted moved to where the container closed, or has no
+    /// container left to close, so its repair goes; one written in the input is dropped
+    fn drop_closer(token: &Token, repairs: &mut Vec<Repair>) {
+        let text = token.text();
+        if !token.span.is_empty() {
+            repairs.push(Repair::new(RepairKind::DroppedCloser, token.span, text, ""));
+            return;
+        }
+        let inserted = repairs.iter().rposition(|r| {
+            r.kind == RepairKind::InsertedCloser && r.span == token.span && r.replacement == text
+        });
+        if let Some(index) = inserted {
+            repairs.remove(index);
         }
+    }
 
-        ParseResult {
-            value: Some(Value::Array(result)),
-            index: idx,
+    /// Record a repair if the comma at `idx` is directly followed by a closer
+    #[inline]
+    fn check_trailing_comma(
+        token_list: &[Token],
+        idx: usize,
+        closer: TokenKind,
+        repairs: &mut Vec<Repair>,
+    ) {
+        let next = token_list.get(idx + 1).map(|t| t.kind);
+        if next == Some(closer) || next == Some(TokenKind::Eof) {
+            let span = token_list[idx].span;
+            repairs.push(Repair::new(RepairKind::RemovedTrailingComma, span, ",", ""));
         }
     }
 }
 
-impl Default for JSONParser {
-    fn default() -> Self {
-        Self::new()
+/// True for a token that starts a value
+fn starts_value(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::LBrace
+            | TokenKind::LBracket
+            | TokenKind::String
+            | TokenKind::Number
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Null
+    )
+}
+
+/// True for a token that ends the slot of a value, leaving it empty if it comes first
+fn ends_slot(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::RBrace | TokenKind::RBracket | TokenKind::Comma | TokenKind::Eof
+    )
+}
+
+/// Value of a scalar token, numbers as `number::exact` keeps them or else as strings
+pub(crate) fn scalar(token: &Token) -> Option<Value> {
+    let text = token.value.clone().unwrap_or_default();
+    match token.kind {
+        TokenKind::String => Some(Value::String(text)),
+        TokenKind::Number => Some(
+            number::exact(&text).map_or(Value::String(text), Value::Number),
+        ),
+        TokenKind::True => Some(Value::Bool(true)),
+        TokenKind::False => Some(Value::Bool(false)),
+        TokenKind::Null => Some(Value::Null),
+        _ => None,
     }
 }
 
+/// Key of a pair written inside an array: a string's contents, or any other scalar as written
+fn pair_key(token: &Token) -> String {
+    match token.kind {
+        TokenKind::String => token.value.clone().unwrap_or_default(),
+        _ => token.text(),
+    }
+}
+
+/// True while the parser is inside the value of a repeated key
+fn muted(frames: &[Frame]) -> bool {
+    frames
+        .iter()
+        .any(|frame| matches!(&frame.body, Body::Object(object) if object.repeat))
+}
+
+/// JSON pointer of the slot the innermost of `frames` is at
+fn pointer(frames: &[Frame], tokens: &[Token], policy: DuplicateKeys) -> String {
+    let mut path = String::new();
+    for frame in frames {
+        match &frame.body {
+            Body::Object(object) => {
+                let (Member::Colon(key) | Member::Value(key) | Member::Garbage(key)) =
+                    object.member
+                else {
+                    continue;
+                };
+                let key = tokens[key].value.as_deref().unwrap_or_default();
+                path.push('/');
+                // Renamed up front so the pointer is where the value ends up
+                if policy == DuplicateKeys::Rename && object.map.contains_key(key) {
+                    push_escaped(&mut path, &duplicates::renamed_key(&object.map, key));
+                } else {
+                    push_escaped(&mut path, key);
+                }
+            }
+            Body::Array(array) => {
+                path.push('/');
+                path.push_str(&array.items.len().to_string());
+                if let Element::Pair(key) | Element::PairGarbage(key) = array.element {
+                    path.push('/');
+                    push_escaped(&mut path, &pair_key(&tokens[key]));
+                }
+            }
+            Body::Skipped(_) => {}
+        }
+    }
+    path
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -280,5 +1299,134 @@ mod tests {
             panic!("Expected array");
         }
     }
+
+    #[test]
+    fn test_parse_array_skips_unexpected_tokens() {
+        let parser = JSONParser::new();
+        let tokens = vec![
+            Token::new(TokenKind::LBracket, Some("[".to_string())),
+            Token::new(TokenKind::Number, Some("1".to_string())),
+            Token::new(TokenKind::RBrace, Some("}".to_string())).with_span(Span::new(2, 3, 1, 3)),
+            Token::new(TokenKind::Comma, Some(",".to_string())),
+            Token::new(TokenKind::Colon, Some(":".to_string())),
+            Token::new(TokenKind::Number, Some("2".to_string())),
+            Token::new(TokenKind::RBracket, Some("]".to_string())),
+            Token::new_simple(TokenKind::Eof),
+        ];
+
+        let mut repairs = Vec::new();
+        let result = parser.parse_with_repairs(&tokens, 0, &mut repairs);
+        assert_eq!(result.value, Some(serde_json::json!([1, 2])));
+        assert_eq!(result.index, 7);
+        let kinds: Vec<_> = repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, [RepairKind::DroppedCloser, RepairKind::DroppedToken]);
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary_precision")]
+    fn test_parse_lossless_numbers() {
+        let parser = JSONParser::new();
+        let raw = [
+            "18446744073709551615",
+            "123456789012345678901234567890",
+            "0.1000000000000000055",
+            "1.50",
+            "-2.5e-7",
+            "1e5",
+            "1E+2",
+            "-0.5E-10",
+        ];
+
+        for text in raw {
+            let tokens = vec![
+                Token::new(TokenKind::Number, Some(text.to_string())),
+                Token::new_simple(TokenKind::Eof),
+            ];
+            let result = parser.parse(&tokens, 0).value.unwrap();
+            assert_eq!(serde_json::to_string(&result).unwrap(), text);
+        }
+
+        // Only a token built by hand can hold text that is no number
+        let tokens = vec![Token::new(TokenKind::Number, Some("1.2.3".to_string()))];
+        let mut repairs = Vec::new();
+        let result = parser.parse_with_repairs(&tokens, 0, &mut repairs);
+        assert_eq!(result.value, Some(Value::from("1.2.3")));
+        assert_eq!(repairs[0].kind, RepairKind::NumberToString);
+    }
+
+    #[test]
+    fn test_parse_missing_colon_actions() {
+        let tokens = vec![
+            Token::new(TokenKind::LBrace, Some("{".to_string())),
+            Token::new(TokenKind::String, Some("a".to_string())),
+            Token::new(TokenKind::RBrace, Some("}".to_string())),
+            Token::new_simple(TokenKind::Eof),
+        ];
+
+        let value = JSONParser::new().parse(&tokens, 0).value.unwrap();
+        assert_eq!(value, serde_json::json!({"a": null}));
+
+        let options = CorrectorOptions::new().missing_colon(RepairAction::Drop);
+        let value = JSONParser::with_options(options).parse(&tokens, 0).value.unwrap();
+        assert_eq!(value, serde_json::json!({}));
+
+        let options = CorrectorOptions::new().missing_colon(RepairAction::Substitute(true.into()));
+        let value = JSONParser::with_options(options).parse(&tokens, 0).value.unwrap();
+        assert_eq!(value, serde_json::json!({"a": true}));
+    }
+
+    #[test]
+    fn test_parse_depth_limit() {
+        let mut tokens: Vec<Token> = "[[[[1]]],2]"
+            .chars()
+            .map(|c| match c {
+                '[' => Token::new(TokenKind::LBracket, Some("[".to_string())),
+                ']' => Token::new(TokenKind::RBracket, Some("]".to_string())),
+                ',' => Token::new(TokenKind::Comma, Some(",".to_string())),
+                _ => Token::new(TokenKind::Number, Some(c.to_string())),
+            })
+            .collect();
+        tokens.push(Token::new_simple(TokenKind::Eof));
+
+        let parser = JSONParser::with_options(CorrectorOptions::new().max_depth(2));
+        let mut repairs = Vec::new();
+        let result = parser.parse_with_repairs(&tokens, 0, &mut repairs);
+
+        assert_eq!(result.value.unwrap(), serde_json::json!([[null], 2]));
+        assert_eq!(result.index, tokens.len() - 1);
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(repairs[0].kind, RepairKind::DepthLimited);
+    }
+
+    #[test]
+    fn test_parse_records_repairs() {
+        let parser = JSONParser::new();
+        let tokens = vec![
+            Token::new(TokenKind::LBrace, Some("{".to_string())).with_span(Span::new(0, 1, 1, 1)),
+            Token::new(TokenKind::String, Some("a".to_string())).with_span(Span::new(1, 4, 1, 2)),
+            Token::new(TokenKind::Number, Some("1".to_string())).with_span(Span::new(5, 6, 1, 6)),
+            Token::new(TokenKind::String, Some("b".to_string())).with_span(Span::new(7, 10, 1, 8)),
+            Token::new(TokenKind::Colon, Some(":".to_string())).with_span(Span::new(10, 11, 1, 11)),
+            Token::new(TokenKind::Number, Some("2".to_string())).with_span(Span::new(11, 12, 1, 12)),
+            Token::new(TokenKind::Comma, Some(",".to_string())).with_span(Span::new(12, 13, 1, 13)),
+            Token::new(TokenKind::RBrace, Some("}".to_string())).with_span(Span::new(13, 14, 1, 14)),
+            Token::new_simple(TokenKind::Eof).with_span(Span::new(14, 14, 1, 15)),
+        ];
+
+        let mut repairs = Vec::new();
+        parser.parse_with_repairs(&tokens, 0, &mut repairs);
+
+        let kinds: Vec<RepairKind> = repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RepairKind::MissingColon,
+                RepairKind::DroppedToken,
+                RepairKind::RemovedTrailingComma,
+            ]
+        );
+        assert_eq!(repairs[0].span, Span::new(5, 5, 1, 6));
+        assert_eq!(repairs[2].span, Span::new(12, 13, 1, 13));
+    }
 }
 
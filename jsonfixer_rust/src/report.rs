@@ -0,0 +1,169 @@
+use crate::token::Span;
+
+/// Kind of repair applied while correcting a JSON input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepairKind {
+    /// A missing `}` or `]` was inserted
+    InsertedCloser,
+    /// A closing bracket without a matching opener was removed
+    DroppedCloser,
+    /// A missing `,` between two values was inserted
+    InsertedComma,
+    /// A `key: value` pair inside an array was wrapped in an object, or the array holding
+    /// it was turned into an object, according to the configured policy
+    PairInArray,
+    /// A `,` directly before a closing bracket was removed
+    RemovedTrailingComma,
+    /// A key without a `:` was given a `null` value
+    MissingColon,
+    /// A key followed by `:` but no value, or an empty array slot, was filled in or removed
+    MissingValue,
+    /// A key repeated within one object was resolved according to the configured policy;
+    /// `replacement` is the member as it ends up in the object
+    DuplicateKey,
+    /// A partial or miscased literal such as `tr` was completed to `true`
+    CompletedLiteral,
+    /// A Python or JavaScript literal such as `None` or `undefined` was converted
+    ConvertedLiteral,
+    /// `NaN` or `Infinity` was replaced according to the configured policy
+    NonFiniteNumber,
+    /// A bare identifier was turned into a string
+    QuotedIdentifier,
+    /// A single-quoted or backtick string was converted to a double-quoted one
+    RequotedString,
+    /// An unterminated string was closed at the end of input
+    ClosedString,
+    /// An escape sequence that JSON does not allow was decoded or dropped
+    InvalidEscape,
+    /// An escape sequence cut off by the end of input was dropped
+    TruncatedEscape,
+    /// A `\u` escape for an unpaired UTF-16 surrogate was replaced with U+FFFD
+    LoneSurrogate,
+    /// Bytes that are not valid UTF-8 were replaced with U+FFFD or dropped
+    InvalidUtf8,
+    /// A multi-byte UTF-8 sequence cut off by the end of input was dropped
+    TruncatedUtf8,
+    /// A malformed number such as `01`, `.5` or `0x1F` was rewritten as valid JSON
+    RepairedNumber,
+    /// A number was turned into a string because it could not be kept as written: its token
+    /// was built by hand with text that is no number, or, without the `arbitrary_precision`
+    /// feature, a float cannot hold it digit for digit
+    NumberToString,
+    /// An unexpected or unknown token was removed
+    DroppedToken,
+    /// A `//`, `#` or `/* */` comment was removed
+    RemovedComment,
+    /// A container nested deeper than the depth limit was replaced with `null`
+    DepthLimited,
+    /// A scalar was converted to the type the schema expects, such as `"42"` to `42`
+    CoercedValue,
+    /// A single value was wrapped in an array because the schema expects one
+    WrappedInArray,
+    /// A truncated or miscased string was completed to the schema's only matching enum value
+    CompletedEnum,
+    /// A missing property or value was filled in with the schema's default
+    InsertedDefault,
+    /// Input past the size limit was ignored; the span covers the ignored bytes and
+    /// `original` is left empty
+    InputLimited,
+    /// Input past the token limit was ignored; `original` is left empty
+    TokenLimited,
+    /// A string longer than the length limit was cut at the limit; `original` is left empty
+    StringLimited,
+    /// Input after the first complete value was discarded
+    DroppedTrailingData,
+    /// A JSON Lines record from which no value could be recovered was removed
+    DroppedRecord,
+    /// Prose or fence markers around an embedded JSON region were discarded
+    RemovedSurroundingText,
+    /// No value could be recovered, an empty object was returned instead
+    EmptyFallback,
+}
+
+/// A single repair with its location in the original input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub kind: RepairKind,
+    /// Location in the original input, zero-width for insertions
+    pub span: Span,
+    /// Original text that was replaced (empty for insertions)
+    pub original: String,
+    /// Replacement text (empty for removals)
+    pub replacement: String,
+}
+
+impl Repair {
+    #[inline]
+    pub fn new(
+        kind: RepairKind,
+        span: Span,
+        original: impl Into<String>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            span,
+            original: original.into(),
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Byte offset in the original input where the repair applies
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.span.start
+    }
+}
+
+/// A place where the repaired value still does not match the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSON pointer to the offending value, empty for the root
+    pub path: String,
+    pub message: String,
+}
+
+/// Corrected JSON together with every repair that was applied to produce it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repaired {
+    pub json: String,
+    pub repairs: Vec<Repair>,
+    /// Schema mismatches left after repair, empty without a schema
+    pub violations: Vec<Violation>,
+}
+
+impl Repaired {
+    /// True if the input was already valid, nothing had to be repaired and the result
+    /// matches the schema
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.repairs.is_empty() && self.violations.is_empty()
+    }
+
+    /// Check whether any repair of the given kind was applied
+    pub fn has_repair(&self, kind: RepairKind) -> bool {
+        self.repairs.iter().any(|r| r.kind == kind)
+    }
+}
+
+/// Every top-level value recovered from one input, with the repairs applied to all of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairedValues {
+    pub values: Vec<String>,
+    pub repairs: Vec<Repair>,
+    /// Schema mismatches left after repair; paths start with the index of the value
+    pub violations: Vec<Violation>,
+}
+
+impl RepairedValues {
+    /// True if the input was already a valid sequence of values matching the schema
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.repairs.is_empty() && self.violations.is_empty()
+    }
+
+    /// Check whether any repair of the given kind was applied
+    pub fn has_repair(&self, kind: RepairKind) -> bool {
+        self.repairs.iter().any(|r| r.kind == kind)
+    }
+}
@@ -0,0 +1,589 @@
+use crate::error::FixError;
+use crate::events::push_escaped;
+use crate::report::{RepairKind, Violation};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Longest chain of `$ref`s that only point at other `$ref`s; a longer one is a cycle
+const MAX_REF_CHAIN: usize = 32;
+
+/// Value of the `type` keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => JsonType::Null,
+            "boolean" => JsonType::Boolean,
+            "integer" => JsonType::Integer,
+            "number" => JsonType::Number,
+            "string" => JsonType::String,
+            "array" => JsonType::Array,
+            "object" => JsonType::Object,
+            _ => return None,
+        })
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match (self, value) {
+            (JsonType::Null, Value::Null)
+            | (JsonType::Boolean, Value::Bool(_))
+            | (JsonType::Number, Value::Number(_))
+            | (JsonType::String, Value::String(_))
+            | (JsonType::Array, Value::Array(_))
+            | (JsonType::Object, Value::Object(_)) => true,
+            (JsonType::Integer, Value::Number(n)) => is_integer(n),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            JsonType::Null => "null",
+            JsonType::Boolean => "boolean",
+            JsonType::Integer => "integer",
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Expected shape of the input, used to steer repairs
+///
+/// Supports a subset of JSON Schema draft 2020-12: `type`, `properties`, `required`,
+/// `additionalProperties`, `items`, `enum`, `const`, `default` and `$ref` into `$defs`
+/// or `definitions`. Other keywords are ignored. Each `$ref` target is compiled once and
+/// shared, so recursive schemas such as trees stay small.
+///
+/// The schema guides the parser and the coercion after it. It also places closers the
+/// brackets leave open: in `{"items": [{"id": 1}, "total": 2` a schema declaring `total`
+/// on the outer object closes the array before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    /// Every compiled subschema, the root first; subschemas refer to each other by index
+    nodes: Vec<Node>,
+}
+
+/// Keywords of one subschema
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Node {
+    /// Allowed types, empty for any
+    types: Vec<JsonType>,
+    properties: Vec<(String, usize)>,
+    required: Vec<String>,
+    /// Schema for properties not listed in `properties`; `None` allows anything
+    additional: Option<usize>,
+    items: Option<usize>,
+    enum_values: Option<Vec<Value>>,
+    default: Option<Value>,
+    /// The `false` schema, which no value matches
+    never: bool,
+}
+
+/// A subschema, read through the schema it was compiled into
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SchemaRef<'a> {
+    nodes: &'a [Node],
+    node: &'a Node,
+    index: usize,
+}
+
+/// Turns a schema document into nodes, compiling each `$ref` target once
+struct Compiler<'a> {
+    root: &'a Value,
+    nodes: Vec<Node>,
+    /// Node of every `$ref` target compiled or being compiled, by JSON pointer
+    targets: HashMap<&'a str, usize>,
+}
+
+impl Schema {
+    /// Build a schema from its JSON text
+    pub fn from_json(text: &str) -> Result<Self, FixError> {
+        let value: Value = serde_json::from_str(text).map_err(|e| FixError::InvalidSchema {
+            reason: e.to_string(),
+        })?;
+        Self::from_value(&value)
+    }
+
+    /// Build a schema from a parsed JSON Schema document
+    pub fn from_value(root: &Value) -> Result<Self, FixError> {
+        let mut compiler = Compiler {
+            root,
+            nodes: Vec::new(),
+            targets: HashMap::new(),
+        };
+        compiler.subschema(root, Some(""))?;
+        Ok(Self {
+            nodes: compiler.nodes,
+        })
+    }
+
+    /// The whole schema, for walking it alongside a value
+    pub(crate) fn root(&self) -> SchemaRef<'_> {
+        self.node(0)
+    }
+
+    /// The subschema `SchemaRef::index` refers to, for holding on to it without a borrow
+    pub(crate) fn node(&self, index: usize) -> SchemaRef<'_> {
+        SchemaRef {
+            nodes: &self.nodes,
+            node: &self.nodes[index],
+            index,
+        }
+    }
+
+    /// Check a value against the schema and list every mismatch, addressed by JSON pointer
+    pub fn validate(&self, value: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        self.root().validate_at(value, &mut String::new(), &mut violations);
+        violations
+    }
+}
+
+impl Default for Schema {
+    /// The `true` schema, which every value matches
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+        }
+    }
+}
+
+impl<'a> Compiler<'a> {
+    /// Compile `value` into a new node and return its index
+    /// `pointer` is the location of `value` if a `$ref` may point at it
+    fn compile(&mut self, value: &'a Value, pointer: Option<&'a str>) -> Result<usize, FixError> {
+        let index = self.nodes.len();
+        self.nodes.push(Node::default());
+        if let Some(pointer) = pointer {
+            self.targets.insert(pointer, index);
+        }
+        self.nodes[index] = self.compile_node(value)?;
+        Ok(index)
+    }
+
+    /// Index of the subschema at `value`, sharing the node of a `$ref` target
+    /// `at` is the location of `value` if a `$ref` may point at it
+    fn subschema(&mut self, value: &'a Value, at: Option<&'a str>) -> Result<usize, FixError> {
+        match self.resolve(value)? {
+            Some((pointer, _)) if self.targets.contains_key(pointer) => Ok(self.targets[pointer]),
+            Some((pointer, target)) => self.compile(target, Some(pointer)),
+            None => self.compile(value, at),
+        }
+    }
+
+    /// Pointer and schema a `$ref` at `value` ends up at, following `$ref`s that point at
+    /// other `$ref`s; `None` if `value` holds no `$ref`
+    fn resolve(&self, value: &'a Value) -> Result<Option<(&'a str, &'a Value)>, FixError> {
+        let mut resolved = None;
+        let mut current = value;
+        for _ in 0..MAX_REF_CHAIN {
+            let Some(reference) = current.get("$ref") else {
+                return Ok(resolved);
+            };
+            let path = reference.as_str().ok_or_else(|| invalid("$ref must be a string".into()))?;
+            let target = path
+                .strip_prefix('#')
+                .and_then(|pointer| Some((pointer, self.root.pointer(pointer)?)))
+                .ok_or_else(|| invalid(format!("unresolved $ref {}", path)))?;
+            resolved = Some(target);
+            current = target.1;
+        }
+        let path = resolved.map_or("", |(pointer, _)| pointer);
+        Err(invalid(format!("$ref cycle through #{}", path)))
+    }
+
+    fn compile_node(&mut self, value: &'a Value) -> Result<Node, FixError> {
+        let object = match value {
+            Value::Bool(true) => return Ok(Node::default()),
+            Value::Bool(false) => {
+                return Ok(Node {
+                    never: true,
+                    ..Node::default()
+                })
+            }
+            Value::Object(object) => object,
+            other => return Err(invalid(format!("expected an object or boolean, found {}", other))),
+        };
+
+        let mut node = Node::default();
+        match object.get("type") {
+            None => {}
+            Some(Value::String(name)) => node.types.push(parse_type(name)?),
+            Some(Value::Array(names)) => {
+                for name in names {
+                    let name = name
+                        .as_str()
+                        .ok_or_else(|| invalid("type must list strings".into()))?;
+                    node.types.push(parse_type(name)?);
+                }
+            }
+            Some(other) => return Err(invalid(format!("invalid type {}", other))),
+        }
+        if let Some(properties) = object.get("properties") {
+            let properties = properties
+                .as_object()
+                .ok_or_else(|| invalid("properties must be an object".into()))?;
+            for (key, property) in properties {
+                node.properties.push((key.clone(), self.subschema(property, None)?));
+            }
+        }
+        if let Some(required) = object.get("required") {
+            let required = required
+                .as_array()
+                .ok_or_else(|| invalid("required must be an array".into()))?;
+            for key in required {
+                let key = key.as_str().ok_or_else(|| invalid("required must list strings".into()))?;
+                node.required.push(key.to_string());
+            }
+        }
+        if let Some(additional) = object.get("additionalProperties") {
+            node.additional = Some(self.subschema(additional, None)?);
+        }
+        if let Some(items) = object.get("items") {
+            node.items = Some(self.subschema(items, None)?);
+        }
+        if let Some(values) = object.get("enum") {
+            let values = values.as_array().ok_or_else(|| invalid("enum must be an array".into()))?;
+            node.enum_values = Some(values.clone());
+        }
+        if let Some(value) = object.get("const") {
+            node.enum_values = Some(vec![value.clone()]);
+        }
+        node.default = object.get("default").cloned();
+        Ok(node)
+    }
+}
+
+impl<'a> SchemaRef<'a> {
+    fn at(self, index: usize) -> Self {
+        Self {
+            node: &self.nodes[index],
+            index,
+            ..self
+        }
+    }
+
+    /// Position of this subschema in the schema it was compiled into
+    pub(crate) fn index(self) -> usize {
+        self.index
+    }
+
+    /// Schema for the property `key` of an object, if it is constrained
+    pub(crate) fn property(self, key: &str) -> Option<Self> {
+        self.node
+            .properties
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|&(_, index)| index)
+            .or(self.node.additional)
+            .map(|index| self.at(index))
+    }
+
+    /// Whether an object with this schema lists `key` among its `properties`
+    pub(crate) fn declares(self, key: &str) -> bool {
+        self.node.properties.iter().any(|(name, _)| name == key)
+    }
+
+    /// Whether `key` looks out of place in an object with this schema: the object lists its
+    /// properties, `key` is not one of them and no `additionalProperties` schema takes it
+    pub(crate) fn rejects_key(self, key: &str) -> bool {
+        let additional = self.node.additional.map(|index| self.at(index));
+        !self.node.properties.is_empty()
+            && !self.declares(key)
+            && additional.is_none_or(|schema| schema.node.never)
+    }
+
+    /// Schema that applies inside a container of type `ty`
+    /// A container the schema only accepts as an array element is read with the item schema,
+    /// since `conform` wraps it afterwards
+    pub(crate) fn container(self, ty: JsonType) -> Self {
+        let types = &self.node.types;
+        let accepts = types.is_empty() || types.contains(&ty);
+        match self.items() {
+            Some(items) if !accepts && types.contains(&JsonType::Array) => items,
+            _ => self,
+        }
+    }
+
+    /// Schema for the elements of an array
+    pub(crate) fn items(self) -> Option<Self> {
+        self.node.items.map(|index| self.at(index))
+    }
+
+    pub(crate) fn default_value(self) -> Option<&'a Value> {
+        self.node.default.as_ref()
+    }
+
+    /// Required properties that are missing from `object` but have a default
+    pub(crate) fn missing_defaults<'b>(
+        self,
+        object: &'b Map<String, Value>,
+    ) -> impl Iterator<Item = (&'a str, &'a Value)> + 'b
+    where
+        'a: 'b,
+    {
+        self.node
+            .required
+            .iter()
+            .filter(|key| !object.contains_key(key.as_str()))
+            .filter_map(move |key| Some((key.as_str(), self.property(key)?.default_value()?)))
+    }
+
+    /// Bring a value closer to the schema: coerce scalars to the expected type, wrap
+    /// single values into arrays and complete truncated enum strings
+    /// Returns the new value and the repair that describes the change, if any
+    pub(crate) fn conform(self, value: &Value) -> Option<(Value, RepairKind)> {
+        let types = &self.node.types;
+        let coerced = if self.accepts_type(value) {
+            None
+        } else if let Some(value) = types.iter().find_map(|&t| coerce(value, t)) {
+            Some((value, RepairKind::CoercedValue))
+        } else if types.contains(&JsonType::Array) {
+            let item = self
+                .items()
+                .and_then(|items| items.conform(value))
+                .map_or_else(|| value.clone(), |(item, _)| item);
+            Some((Value::Array(vec![item]), RepairKind::WrappedInArray))
+        } else {
+            None
+        };
+
+        let current = coerced.as_ref().map_or(value, |(value, _)| value);
+        match self.complete_enum(current) {
+            Some(completed) => Some((completed, RepairKind::CompletedEnum)),
+            None => coerced,
+        }
+    }
+
+    fn validate_at(self, value: &Value, path: &mut String, out: &mut Vec<Violation>) {
+        let mut report = |message: String| {
+            out.push(Violation {
+                path: path.clone(),
+                message,
+            })
+        };
+        if self.node.never {
+            report("no value is allowed here".to_string());
+            return;
+        }
+        if !self.accepts_type(value) {
+            let expected: Vec<String> = self.node.types.iter().map(JsonType::to_string).collect();
+            report(format!("expected {}, found {}", expected.join(" or "), type_name(value)));
+            return;
+        }
+        if let Some(values) = &self.node.enum_values {
+            if !values.contains(value) {
+                report(format!("{} is not one of the allowed values", value));
+            }
+        }
+
+        match value {
+            Value::Object(object) => {
+                let required = &self.node.required;
+                for key in required.iter().filter(|key| !object.contains_key(key.as_str())) {
+                    report(format!("missing required property \"{}\"", key));
+                }
+                for (key, item) in object {
+                    let len = path.len();
+                    path.push('/');
+                    push_escaped(path, key);
+                    if let Some(schema) = self.property(key) {
+                        schema.validate_at(item, path, out);
+                    }
+                    path.truncate(len);
+                }
+            }
+            Value::Array(items) => {
+                if let Some(schema) = self.items() {
+                    for (i, item) in items.iter().enumerate() {
+                        let len = path.len();
+                        path.push('/');
+                        path.push_str(&i.to_string());
+                        schema.validate_at(item, path, out);
+                        path.truncate(len);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn accepts_type(self, value: &Value) -> bool {
+        let types = &self.node.types;
+        types.is_empty() || types.iter().any(|t| t.matches(value))
+    }
+
+    /// Unique enum string that `value` is a truncated or miscased spelling of
+    fn complete_enum(self, value: &Value) -> Option<Value> {
+        let values = self.node.enum_values.as_ref()?;
+        let text = value.as_str().filter(|text| !text.is_empty())?;
+        if values.contains(value) {
+            return None;
+        }
+        let lower = text.to_lowercase();
+        let mut candidates = values
+            .iter()
+            .filter(|v| v.as_str().is_some_and(|v| v.to_lowercase().starts_with(&lower)));
+        match (candidates.next(), candidates.next()) {
+            (Some(only), None) => Some(only.clone()),
+            _ => None,
+        }
+    }
+}
+
+fn invalid(reason: String) -> FixError {
+    FixError::InvalidSchema { reason }
+}
+
+fn parse_type(name: &str) -> Result<JsonType, FixError> {
+    JsonType::from_name(name).ok_or_else(|| FixError::InvalidSchema {
+        reason: format!("unknown type {}", name),
+    })
+}
+
+/// Convert a scalar to the given type when the conversion loses nothing
+fn coerce(value: &Value, to: JsonType) -> Option<Value> {
+    match (to, value) {
+        (JsonType::Integer, Value::String(s)) => {
+            let number: Number = s.trim().parse().ok()?;
+            is_integer(&number).then_some(Value::Number(number))
+        }
+        (JsonType::Integer, Value::Number(n)) => {
+            // `42.0` is an integer written as a decimal
+            let f = n.as_f64()?;
+            (f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0)
+                .then(|| Value::Number(Number::from(f as i64)))
+        }
+        (JsonType::Number, Value::String(s)) => s.trim().parse().ok().map(Value::Number),
+        (JsonType::Boolean, Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        (JsonType::Null, Value::String(s)) if s.trim().eq_ignore_ascii_case("null") => {
+            Some(Value::Null)
+        }
+        (JsonType::String, Value::Number(n)) => Some(Value::String(n.to_string())),
+        (JsonType::String, Value::Bool(b)) => Some(Value::String(b.to_string())),
+        _ => None,
+    }
+}
+
+/// True if the number is written without a fraction or exponent
+fn is_integer(number: &Number) -> bool {
+    !number.to_string().contains(['.', 'e', 'E'])
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if is_integer(n) => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_conform() {
+        let schema = Schema::from_value(&json!({
+            "type": "object",
+            "properties": {
+                "age": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "status": {"enum": ["active", "archived", "deleted"]},
+                "ok": {"type": ["boolean", "null"]}
+            }
+        }))
+        .unwrap();
+        let conform = |key: &str, value: Value| schema.root().property(key).unwrap().conform(&value);
+
+        assert_eq!(conform("age", json!("42")), Some((json!(42), RepairKind::CoercedValue)));
+        assert_eq!(conform("age", json!(42.0)), Some((json!(42), RepairKind::CoercedValue)));
+        assert_eq!(conform("age", json!("4.5")), None);
+        assert_eq!(conform("tags", json!(7)), Some((json!(["7"]), RepairKind::WrappedInArray)));
+        assert_eq!(conform("status", json!("arch")), Some((json!("archived"), RepairKind::CompletedEnum)));
+        assert_eq!(conform("status", json!("a")), None);
+        assert_eq!(conform("ok", json!("TRUE")), Some((json!(true), RepairKind::CoercedValue)));
+        assert_eq!(conform("ok", json!(null)), None);
+    }
+
+    #[test]
+    fn test_schema_validate_and_refs() {
+        let schema = Schema::from_value(&json!({
+            "$defs": {"item": {"type": "object", "required": ["id"], "additionalProperties": false,
+                               "properties": {"id": {"type": "integer"}}}},
+            "type": "array",
+            "items": {"$ref": "#/$defs/item"}
+        }))
+        .unwrap();
+
+        let violations = schema.validate(&json!([{"id": 1}, {"x/y": 2}, {"id": "a"}]));
+        let found: Vec<(&str, &str)> =
+            violations.iter().map(|v| (v.path.as_str(), v.message.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("/1", "missing required property \"id\""),
+                ("/1/x~1y", "no value is allowed here"),
+                ("/2/id", "expected integer, found string"),
+            ]
+        );
+
+        assert!(Schema::from_value(&json!({"type": "text"})).is_err());
+        assert!(Schema::from_value(&json!({"$defs": {"a": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"})).is_err());
+        assert!(Schema::from_value(&json!({"$ref": "#/$defs/missing"})).is_err());
+        assert!(Schema::from_json("{").is_err());
+    }
+
+    #[test]
+    fn test_schema_recursive_refs_compile_once() {
+        let schema = Schema::from_value(&json!({
+            "$defs": {
+                "node": {
+                    "type": "object",
+                    "required": ["value"],
+                    "properties": {
+                        "value": {"type": "integer"},
+                        "left": {"$ref": "#/$defs/node"},
+                        "right": {"$ref": "#/$defs/node"}
+                    }
+                }
+            },
+            "$ref": "#/$defs/node"
+        }))
+        .unwrap();
+        // The node definition and its value property
+        assert_eq!(schema.nodes.len(), 2);
+
+        let tree = json!({"value": 1, "left": {"value": 2, "right": {"value": "3"}}, "right": {}});
+        let found: Vec<String> = schema.validate(&tree).into_iter().map(|v| v.path).collect();
+        assert_eq!(found, ["/left/right/value", "/right"]);
+
+        let leaf = schema.root().property("left").unwrap().property("right").unwrap();
+        assert_eq!(leaf.property("value").unwrap().conform(&json!("3")).unwrap().0, json!(3));
+    }
+}
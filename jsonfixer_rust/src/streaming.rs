@@ -0,0 +1,710 @@
+use crate::autocorrector::JSONAutoCorrector;
+use crate::events::{EventParser, StreamEvent};
+use crate::options::CorrectorOptions;
+use crate::report::{Repair, RepairKind, Repaired};
+use crate::token::{Span, Token, TokenKind};
+use crate::token_fixer::BracketStack;
+use crate::tokenizer::JSONTokenizer;
+
+/// Longest escape that has to be decoded in one piece: a `\uXXXX\uXXXX` surrogate pair
+const MAX_ESCAPE_LEN: usize = 12;
+
+/// A string whose closing quote has not arrived yet
+struct OpenString {
+    quote: u8,
+    /// Zero-width span at the opening quote
+    start: Span,
+    /// Contents decoded so far
+    value: String,
+}
+
+/// A comment that reaches the end of the input so far
+struct OpenComment {
+    start: usize,
+    /// `/* ... */` rather than a line comment
+    block: bool,
+    /// Input already searched for the end of the comment
+    scanned: usize,
+}
+
+impl OpenComment {
+    /// Whether the comment ends within `input`, searching only what arrived since last time
+    fn ends_in(&mut self, input: &[u8]) -> bool {
+        let ended = if self.block {
+            // The `*/` may straddle two pushes, but the `*` of `/*` does not start it
+            let from = self.scanned.saturating_sub(1).max(self.start + 2);
+            input[from..].windows(2).any(|w| w == b"*/")
+        } else {
+            input[self.scanned..].contains(&b'\n')
+        };
+        self.scanned = input.len();
+        ended
+    }
+}
+
+/// Incremental corrector for JSON that arrives in chunks, such as streamed LLM output
+///
+/// Each `push` only tokenizes the new chunk plus the unfinished token before it; open
+/// strings and the bracket stack are kept between chunks. `snapshot` gives the same
+/// result as `JSONAutoCorrector::autocorrect` on everything pushed so far.
+///
+/// With `CorrectorOptions::extract_embedded`, the region to repair can change as more
+/// text arrives, so each `snapshot` repairs the whole input again instead.
+pub struct StreamingCorrector {
+    corrector: JSONAutoCorrector,
+    tokenizer: JSONTokenizer,
+    /// Everything pushed so far
+    input: Vec<u8>,
+    /// Zero-width span at the first byte not yet turned into tokens
+    origin: Span,
+    /// Finished tokens, balanced as far as they go
+    tokens: Vec<Token>,
+    brackets: BracketStack,
+    /// Repairs made to the finished tokens
+    repairs: Vec<Repair>,
+    string: Option<OpenString>,
+    comment: Option<OpenComment>,
+    /// The token limit was reached, so the rest of the input is left to `snapshot`
+    limited: bool,
+    /// Tokens taken from the input so far, before any closers are inserted
+    token_count: usize,
+    /// Bytes dropped past the input size limit
+    overflow: usize,
+    /// Event tracking, only used behind an `EventStream`
+    events: Option<EventParser>,
+}
+
+impl StreamingCorrector {
+    pub fn new() -> Self {
+        Self::with_options(CorrectorOptions::default())
+    }
+
+    /// Create a streaming corrector with a custom repair policy
+    pub fn with_options(options: CorrectorOptions) -> Self {
+        Self {
+            tokenizer: JSONTokenizer::with_options(options.clone()),
+            corrector: JSONAutoCorrector::with_options(options),
+            input: Vec::new(),
+            origin: Span::new(0, 0, 1, 1),
+            tokens: Vec::new(),
+            brackets: BracketStack::new(),
+            repairs: Vec::new(),
+            string: None,
+            comment: None,
+            limited: false,
+            token_count: 0,
+            overflow: 0,
+            events: None,
+        }
+    }
+
+    /// Create a streaming corrector that also reports events as values arrive
+    /// Events follow the input from its first byte, so extraction is turned off
+    pub(crate) fn with_events(options: CorrectorOptions) -> Self {
+        let options = options.extract_embedded(false);
+        Self {
+            events: Some(EventParser::new(options.clone())),
+            ..Self::with_options(options)
+        }
+    }
+
+    /// Events produced since the last call
+    pub(crate) fn take_events(&mut self) -> Vec<StreamEvent> {
+        self.events.as_mut().map(EventParser::take).unwrap_or_default()
+    }
+
+    /// Treat the input as ended and return the events that completes
+    pub(crate) fn finish_events(&mut self) -> Vec<StreamEvent> {
+        self.snapshot_inner(true);
+        self.take_events()
+    }
+
+    /// Append the next chunk of input
+    pub fn push(&mut self, chunk: &str) {
+        self.push_bytes(chunk.as_bytes());
+    }
+
+    /// Append the next chunk of raw bytes; a UTF-8 character may be split across chunks
+    /// Bytes past the input size limit are dropped
+    pub fn push_bytes(&mut self, chunk: &[u8]) {
+        let room = match self.corrector.options().input_size_limit() {
+            Some(limit) => limit.saturating_sub(self.input.len()),
+            None => chunk.len(),
+        };
+        let (chunk, dropped) = chunk.split_at(chunk.len().min(room));
+        self.overflow += dropped.len();
+        self.input.extend_from_slice(chunk);
+        if self.corrector.options().extracts_embedded() {
+            return;
+        }
+        loop {
+            let switched = if self.string.is_some() {
+                self.advance_string()
+            } else {
+                self.advance_tokens()
+            };
+            if !switched {
+                break;
+            }
+        }
+    }
+
+    /// Best-effort repaired JSON for everything pushed so far
+    pub fn snapshot(&mut self) -> String {
+        self.snapshot_with_report().json
+    }
+
+    /// Best-effort repaired JSON for everything pushed so far, with every repair applied
+    pub fn snapshot_with_report(&mut self) -> Repaired {
+        self.snapshot_inner(false)
+    }
+
+    /// Repair everything pushed so far; `finish` also reports the tail as events
+    fn snapshot_inner(&mut self, finish: bool) -> Repaired {
+        if self.corrector.options().extracts_embedded() {
+            return self.extracted();
+        }
+        let origin = self.origin;
+        let pending = &self.input[origin.start..];
+        let end = advance(origin, pending);
+        let mut repairs = self.repairs.clone();
+        repairs.extend(self.overflow_repair(end));
+        let mut brackets = self.brackets.clone();
+        let committed = self.tokens.len();
+
+        // Tokenize the unfinished tail as if the input ended here
+        let mut tail_repairs = Vec::new();
+        let tail = match &self.string {
+            Some(open) => {
+                let (part, _, _) =
+                    self.tokenizer.string_contents(pending, open.quote, &mut tail_repairs);
+                let mut token = string_token(open, part, end.start);
+                self.tokenizer.limit_string(&mut token, &mut repairs);
+                repairs.extend(quote_repair(open, token.span));
+                repairs.push(Repair::new(RepairKind::ClosedString, end, "", "\""));
+                vec![token]
+            }
+            None => {
+                let counted = self.token_count;
+                let mut tail = self.tokenizer.tokenize_counted(pending, counted, &mut tail_repairs);
+                tail.pop(); // Eof
+                tail.into_iter()
+                    .map(|t| {
+                        let span = t.span.offset_by(origin);
+                        t.with_span(span)
+                    })
+                    .collect()
+            }
+        };
+        repairs.extend(tail_repairs.into_iter().map(|r| shift(r, origin)));
+        for token in tail {
+            brackets.push(token, &mut self.tokens, &mut repairs);
+        }
+        brackets.close_all(end, &mut self.tokens, &mut repairs);
+        self.tokens.push(Token::new_simple(TokenKind::Eof).with_span(end));
+        if let Some(events) = self.events.as_mut().filter(|_| finish) {
+            events.finish(&self.tokens);
+        }
+
+        let parsed = self.corrector.parse_fixed(&self.tokens, &self.input, &mut repairs);
+        self.tokens.truncate(committed);
+        let value = self.corrector.finish(parsed, &self.input, &mut repairs);
+        self.corrector.report(&value, repairs)
+    }
+
+    /// Repair the whole input in one go, for extraction, which has to see all of it to
+    /// pick the region
+    fn extracted(&mut self) -> Repaired {
+        let end = advance(Span::new(0, 0, 1, 1), &self.input);
+        let mut repairs: Vec<Repair> = self.overflow_repair(end).into_iter().collect();
+        let value = self.corrector.correct(&self.input, &mut repairs);
+        self.corrector.report(&value, repairs)
+    }
+
+    /// Report of the bytes dropped past the input size limit, which would follow `end`
+    fn overflow_repair(&self, end: Span) -> Option<Repair> {
+        let span = Span {
+            end: end.start + self.overflow,
+            ..end
+        };
+        (self.overflow > 0).then(|| Repair::new(RepairKind::InputLimited, span, "", ""))
+    }
+
+    /// Tokenize pending input up to the last token more input could still change
+    /// Returns true if an unterminated string was entered
+    fn advance_tokens(&mut self) -> bool {
+        if self.limited {
+            return false;
+        }
+        // Until an open comment ends, tokenizing it again would only find the same comment
+        if let Some(comment) = self.comment.as_mut() {
+            if !comment.ends_in(&self.input) {
+                return false;
+            }
+            self.comment = None;
+        }
+        let origin = self.origin;
+        let pending = &self.input[origin.start..];
+        let len = pending.len();
+        let mut repairs = Vec::new();
+        let mut tokens = self.tokenizer.tokenize_counted(pending, self.token_count, &mut repairs);
+        tokens.pop(); // Eof
+
+        // Anything reaching the end of input may still grow: a literal, string or comment
+        let growing = tokens
+            .iter()
+            .filter(|t| !is_punctuation(t.kind))
+            .map(|t| t.span)
+            .chain(repairs.iter().map(|r| r.span));
+        let mut cut = growing
+            .filter(|span| span.end == len && !span.is_empty())
+            .map(|span| span.start)
+            .min()
+            .unwrap_or(len);
+        // A literal glued to the held bytes, such as a word cut inside a multi-byte letter
+        if cut < len && !is_quote(pending[cut]) {
+            while let Some(token) = tokens
+                .iter()
+                .find(|t| t.span.end == cut && t.span.start < cut && !is_punctuation(t.kind))
+            {
+                cut = token.span.start;
+            }
+        }
+        let opens_string = cut < len
+            && is_quote(pending[cut])
+            && repairs.iter().any(|r| r.kind == RepairKind::ClosedString);
+        let block = pending[cut..].starts_with(b"/*");
+        let opens_comment = repairs.iter().any(|r| {
+            r.kind == RepairKind::RemovedComment && r.span.start == cut && r.span.end == len
+        }) && !(block && len - cut >= 4 && pending.ends_with(b"*/"));
+        self.limited = repairs.iter().any(|r| r.kind == RepairKind::TokenLimited);
+
+        let (finished, held): (Vec<Token>, Vec<Token>) =
+            tokens.into_iter().partition(|t| t.span.start < cut);
+        self.token_count += finished.len();
+        for token in finished {
+            let span = token.span.offset_by(origin);
+            self.brackets.push(token.with_span(span), &mut self.tokens, &mut self.repairs);
+        }
+        if let Some(events) = self.events.as_mut() {
+            events.advance(&self.tokens);
+            match held.first() {
+                _ if opens_string => events.open_string(&self.tokens),
+                Some(token) => events.partial(&self.tokens, token),
+                None => {}
+            }
+        }
+        let finished = repairs.into_iter().filter(|r| r.span.start < cut);
+        self.repairs.extend(finished.map(|r| shift(r, origin)));
+        self.advance_to(origin.start + cut);
+
+        if opens_comment {
+            self.comment = Some(OpenComment {
+                start: self.origin.start,
+                block,
+                scanned: self.input.len(),
+            });
+        }
+        if opens_string {
+            let start = self.origin;
+            self.string = Some(OpenString {
+                quote: self.input[start.start],
+                start,
+                value: String::new(),
+            });
+            self.advance_to(start.start + 1);
+        }
+        opens_string
+    }
+
+    /// Decode the open string up to its closing quote, or as far as possible without
+    /// splitting an escape or a UTF-8 character. Returns true if the string closed
+    fn advance_string(&mut self) -> bool {
+        let origin = self.origin;
+        let Some(open) = self.string.as_mut() else {
+            return false;
+        };
+        let pending = &self.input[origin.start..];
+        let (end, closed) = decodable_len(pending, open.quote);
+
+        let mut repairs = Vec::new();
+        let (part, _, _) = self
+            .tokenizer
+            .string_contents(&pending[..end], open.quote, &mut repairs);
+        let shown = open.value.len();
+        open.value.push_str(&part);
+        if let Some(limit) = self.corrector.options().string_length_limit() {
+            cap_string(&mut open.value, limit);
+        }
+        if let Some(events) = self.events.as_mut() {
+            let limit = self.corrector.options().string_length_limit();
+            events.append(&self.tokens, within(&open.value, shown, limit));
+        }
+        self.repairs.extend(repairs.into_iter().map(|r| shift(r, origin)));
+        self.advance_to(origin.start + end);
+
+        if closed {
+            if let Some(open) = self.string.take() {
+                let mut token = string_token(&open, String::new(), self.origin.start);
+                self.tokenizer.limit_string(&mut token, &mut self.repairs);
+                self.repairs.extend(quote_repair(&open, token.span));
+                self.token_count += 1;
+                self.brackets.push(token, &mut self.tokens, &mut self.repairs);
+                if let Some(events) = self.events.as_mut() {
+                    events.advance(&self.tokens);
+                }
+            }
+        }
+        closed
+    }
+
+    /// Move the start of pending input forward, keeping line and column in step
+    fn advance_to(&mut self, to: usize) {
+        self.origin = advance(self.origin, &self.input[self.origin.start..to]);
+    }
+}
+
+impl Default for StreamingCorrector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Finish an open string token at `end`, appending the last decoded part
+fn string_token(open: &OpenString, part: String, end: usize) -> Token {
+    let mut value = open.value.clone();
+    value.push_str(&part);
+    let span = Span { end, ..open.start };
+    Token::new(TokenKind::String, Some(value)).with_span(span)
+}
+
+/// Keep at most one character past the length limit of an open string
+/// That character tells `JSONTokenizer::limit_string` to truncate and report it later
+fn cap_string(value: &mut String, limit: usize) {
+    if let Some(end) = (limit + 1..value.len()).find(|&i| value.is_char_boundary(i)) {
+        value.truncate(end);
+    }
+}
+
+/// The part of `value` after `from` that falls within the length limit
+fn within(value: &str, from: usize, limit: Option<usize>) -> &str {
+    let end = match limit {
+        Some(limit) if value.len() > limit => {
+            (0..=limit).rev().find(|&i| value.is_char_boundary(i)).unwrap_or(0)
+        }
+        _ => value.len(),
+    };
+    value.get(from.min(end)..end).unwrap_or_default()
+}
+
+/// Repair for a single-quoted or backtick string, as the tokenizer reports it
+fn quote_repair(open: &OpenString, span: Span) -> Option<Repair> {
+    let original = (open.quote as char).to_string();
+    (open.quote != b'"').then(|| Repair::new(RepairKind::RequotedString, span, original, "\""))
+}
+
+/// Length of the open string's bytes that can be decoded now, and whether the closing
+/// quote is among them. Escapes near the end wait for up to `MAX_ESCAPE_LEN` bytes,
+/// a split UTF-8 character waits for the rest of its bytes
+fn decodable_len(bytes: &[u8], quote: u8) -> (usize, bool) {
+    let mut held = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => {
+                if held.is_none() && i + MAX_ESCAPE_LEN > bytes.len() {
+                    held = Some(i);
+                }
+                i += 2;
+            }
+            b if b == quote => return (i + 1, true),
+            _ => i += 1,
+        }
+    }
+    match held {
+        Some(end) => (end, false),
+        None => (utf8_boundary(bytes), false),
+    }
+}
+
+/// End of the last complete UTF-8 character, leaving out a trailing partial one
+fn utf8_boundary(bytes: &[u8]) -> usize {
+    let end = bytes.len();
+    let continuation = bytes.iter().rev().take(3).take_while(|&&b| b & 0xC0 == 0x80).count();
+    let Some(lead) = end.checked_sub(continuation + 1) else {
+        return end;
+    };
+    let needed = match bytes[lead] {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    };
+    if needed > continuation + 1 {
+        lead
+    } else {
+        end
+    }
+}
+
+/// Zero-width span just after `bytes`, which start at `span`
+fn advance(span: Span, bytes: &[u8]) -> Span {
+    let (mut line, mut column) = (span.line, span.column);
+    for &b in bytes {
+        if b == b'\n' {
+            line += 1;
+            column = 1;
+        } else if b & 0xC0 != 0x80 {
+            column += 1;
+        }
+    }
+    let end = span.start + bytes.len();
+    Span::new(end, end, line, column)
+}
+
+/// Move a repair measured from `origin` into whole-input coordinates
+#[inline]
+fn shift(repair: Repair, origin: Span) -> Repair {
+    Repair {
+        span: repair.span.offset_by(origin),
+        ..repair
+    }
+}
+
+#[inline]
+fn is_punctuation(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::LBrace
+            | TokenKind::RBrace
+            | TokenKind::LBracket
+            | TokenKind::RBracket
+            | TokenKind::Colon
+            | TokenKind::Comma
+    )
+}
+
+#[inline]
+fn is_quote(b: u8) -> bool {
+    matches!(b, b'"' | b'\'' | b'`')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{ArrayPairs, DuplicateKeys, RepairAction};
+
+    /// Push `input` in chunks of `size` bytes, comparing every snapshot with a batch run
+    fn assert_matches_batch(input: &str, size: usize) {
+        assert_matches_batch_with(CorrectorOptions::new(), input, size);
+    }
+
+    fn assert_matches_batch_with(options: CorrectorOptions, input: &str, size: usize) {
+        let mut streaming = StreamingCorrector::with_options(options.clone());
+        let mut batch = JSONAutoCorrector::with_options(options);
+        let bytes = input.as_bytes();
+
+        let mut end = 0;
+        for chunk in bytes.chunks(size) {
+            streaming.push_bytes(chunk);
+            end += chunk.len();
+            let expected = batch.autocorrect_bytes_with_report(&bytes[..end]);
+            assert_eq!(streaming.snapshot_with_report(), expected, "prefix {:?}", &bytes[..end]);
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_batch() {
+        let inputs = [
+            r#"{"name": "Ann", "tags": ["a", 'b', `c`], "ok": tru, "n": -1.5e3, nested: {"x": [1, 2,]}}"#,
+            r#"{"s": "esc \" \\ \n é 😀 \x41 \q", "é": "日本語 😀"} // done"#,
+            "[1, 2 /* note */, {\"a\": None} ] ] {\"extra\": 1}",
+            "# header\n{\"a\": [1, {\"b\": \"multi\nline\"}}",
+        ];
+        for input in inputs {
+            for size in [1, 2, 3, 5, 8, 64] {
+                assert_matches_batch(input, size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_limits_match_batch() {
+        let options = CorrectorOptions::new()
+            .max_input_size(40)
+            .max_string_length(5)
+            .max_depth(2);
+        let input = r#"{"short": "abc", 'long': "abcdéfgh", "deep": [[[1]]], "cut": "off"}"#;
+        for size in [1, 3, 8, 64] {
+            assert_matches_batch_with(options.clone(), input, size);
+        }
+
+        // The token limit counts tokens across chunks, not within each one
+        let options = CorrectorOptions::new().max_tokens(8);
+        for size in [1, 2, 64] {
+            assert_matches_batch_with(options.clone(), "[1,2,3,4,5,6,7,8,9] ", size);
+        }
+        let mut streaming = StreamingCorrector::with_options(options);
+        "[1,2,3,4,5,6,7,8,9]".split(',').for_each(|part| streaming.push(&format!("{},", part)));
+        assert_eq!(streaming.snapshot(), "[1,2,3,4]");
+    }
+
+    #[test]
+    fn test_streaming_extraction_matches_batch() {
+        let options = CorrectorOptions::new().extract_embedded(true);
+        let inputs = [
+            r#"Sure: {"a": 1} ok"#,
+            "Here you go:\n```json\n{\"name\": \"Ann\", \"tags\": [\"a\"\n```\nAnything else?",
+            r#"First [1, 2] then {"b": {"c": tr"#,
+        ];
+        for input in inputs {
+            for size in [1, 4, 64] {
+                assert_matches_batch_with(options.clone(), input, size);
+            }
+        }
+        for size in [1, 64] {
+            assert_matches_batch_with(options.clone().max_input_size(12), inputs[0], size);
+        }
+
+        let mut streaming = StreamingCorrector::with_options(options);
+        streaming.push(inputs[0]);
+        assert_eq!(streaming.snapshot(), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn test_streaming_caps_open_strings() {
+        let options = CorrectorOptions::new().max_string_length(4);
+        let mut streaming = StreamingCorrector::with_options(options);
+        streaming.push(r#"["ab"#);
+        for _ in 0..100 {
+            streaming.push("cdéf");
+        }
+        assert!(streaming.string.as_ref().is_some_and(|open| open.value.len() <= 6));
+        assert_eq!(streaming.snapshot(), r#"["abcd"]"#);
+        streaming.push(r#"", "x"]"#);
+        assert_eq!(streaming.snapshot(), r#"["abcd","x"]"#);
+    }
+
+    #[test]
+    fn test_streaming_duplicate_keys_match_batch() {
+        let input = r#"{"a": 1, "a": [2], "o": {"x": 1}, "o": {"y": 2}, "a": 3, "a_2": 4}"#;
+        let policies = [
+            DuplicateKeys::KeepFirst,
+            DuplicateKeys::Collect,
+            DuplicateKeys::Merge,
+            DuplicateKeys::Rename,
+        ];
+        for policy in policies {
+            let options = CorrectorOptions::new().duplicate_keys(policy);
+            for size in [1, 4, 64] {
+                assert_matches_batch_with(options.clone(), input, size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_missing_values_match_batch() {
+        let input = r#"{"a":, "b": [1,,2, tr], "c": {"d": }, "e": [,]}"#;
+        let actions = [RepairAction::Repair, RepairAction::Drop];
+        for action in actions {
+            let options = CorrectorOptions::new()
+                .missing_values(action.clone())
+                .partial_literals(action);
+            for size in [1, 3, 64] {
+                assert_matches_batch_with(options.clone(), input, size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_stray_closers_match_batch() {
+        let inputs = [
+            r#"{"a": [1, }, 2], "b": 1}"#,
+            "[1, }, 2, [3 }, 4]",
+            r#"{"a": [1, 2}, "b"]"#,
+            r#"{"a":[}, "b":1}"#,
+            r#"{"a":[1,2,}, "b":3}"#,
+            r#"{"a": [1, }, "b""#,
+        ];
+        for input in inputs {
+            for size in [1, 3, 64] {
+                assert_matches_batch(input, size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_array_pairs_match_batch() {
+        let input = r#"{"a": ["x": 1, "y": [2]], "b": [0, 1: tr, "c":, @ 3]}"#;
+        for policy in [ArrayPairs::Object, ArrayPairs::Wrap] {
+            let options = CorrectorOptions::new()
+                .array_pairs(policy)
+                .missing_values(RepairAction::Drop);
+            for size in [1, 3, 64] {
+                assert_matches_batch_with(options.clone(), input, size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_keeps_pending_input_small() {
+        let mut streaming = StreamingCorrector::new();
+        streaming.push(r#"{"text": ""#);
+        for _ in 0..1000 {
+            streaming.push("lorem ipsum \\\"dolor\\\" sit amet ");
+            assert!(streaming.input.len() - streaming.origin.start <= MAX_ESCAPE_LEN);
+        }
+        streaming.push("\", \"done\": tr");
+        #[cfg(feature = "preserve_order")]
+        assert!(streaming.snapshot().ends_with(r#"sit amet ","done":true}"#));
+        assert!(streaming.string.is_none());
+    }
+
+    #[test]
+    fn test_streaming_does_not_rescan_open_comments_or_limited_input() {
+        let comments = [("[1, /* note", " */ 2]"), ("[1, // note", "\n 2]"), ("[1, # x", "\n2]")];
+        for (input, tail) in comments {
+            let mut streaming = StreamingCorrector::new();
+            let mut batch = JSONAutoCorrector::new();
+            let mut whole = input.to_string();
+            streaming.push(input);
+            for _ in 0..100 {
+                streaming.push(" more*");
+                whole.push_str(" more*");
+                assert!(streaming.comment.as_ref().is_some_and(|c| c.scanned == whole.len()));
+            }
+            assert_eq!(streaming.snapshot_with_report(), batch.autocorrect_with_report(&whole));
+            streaming.push(tail);
+            whole.push_str(tail);
+            assert!(streaming.comment.is_none());
+            assert_eq!(streaming.snapshot_with_report(), batch.autocorrect_with_report(&whole));
+        }
+
+        let options = CorrectorOptions::new().max_tokens(4);
+        let mut streaming = StreamingCorrector::with_options(options);
+        streaming.push("[1, 2, 3");
+        let origin = streaming.origin;
+        for _ in 0..100 {
+            streaming.push(", 4");
+        }
+        assert!(streaming.limited);
+        assert_eq!(streaming.origin, origin);
+        assert_eq!(streaming.snapshot(), "[1,2]");
+    }
+
+    #[test]
+    fn test_streaming_split_utf8_and_escape() {
+        let mut streaming = StreamingCorrector::new();
+        let bytes = "[\"😀\\ud83d\\ude00\"]".as_bytes();
+        streaming.push_bytes(&bytes[..4]);
+        assert_eq!(streaming.snapshot(), r#"[""]"#);
+        streaming.push_bytes(&bytes[4..6]);
+        assert_eq!(streaming.snapshot(), r#"["😀"]"#);
+        // Half a surrogate pair reads as a lone surrogate until the rest arrives
+        streaming.push_bytes(&bytes[6..14]);
+        assert_eq!(streaming.snapshot(), "[\"😀\u{fffd}\"]");
+        streaming.push_bytes(&bytes[14..]);
+        assert_eq!(streaming.snapshot(), r#"["😀😀"]"#);
+        assert!(streaming.snapshot_with_report().is_valid());
+    }
+}
@@ -14,24 +14,78 @@ pub enum TokenKind {
     Null,      // null
     Eof,       // End of input
     Unknown,   // Unknown token
+    Dropped,   // Gap left by a literal the repair policy removed
+    Stray,     // Closer that closes nothing, already reported as dropped
 }
 
-/// Represents a single token with its type and optional value
+/// Location of a token in the original input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    /// Byte offset of the first byte
+    pub start: usize,
+    /// Byte offset one past the last byte
+    pub end: usize,
+    /// 1-based line number of `start`
+    pub line: usize,
+    /// 1-based column of `start`, counted in characters
+    pub column: usize,
+}
+
+impl Span {
+    #[inline]
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self { start, end, line, column }
+    }
+
+    /// Zero-width span at the start of this span, used for insertions
+    #[inline]
+    pub fn empty(self) -> Self {
+        Self { end: self.start, ..self }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Translate a span measured inside `origin` into the coordinates of the whole input
+    #[inline]
+    pub(crate) fn offset_by(self, origin: Span) -> Self {
+        Self {
+            start: self.start + origin.start,
+            end: self.end + origin.start,
+            line: self.line + origin.line - 1,
+            column: if self.line == 1 {
+                self.column + origin.column - 1
+            } else {
+                self.column
+            },
+        }
+    }
+}
+
+/// Represents a single token with its type, optional value and source span
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
     pub value: Option<String>,
+    pub span: Span,
 }
 
 impl Token {
     #[inline]
     pub fn new(kind: TokenKind, value: Option<String>) -> Self {
-        Self { kind, value }
+        Self { kind, value, span: Span::default() }
     }
 
     #[inline]
     pub fn new_simple(kind: TokenKind) -> Self {
-        Self { kind, value: None }
+        Self { kind, value: None, span: Span::default() }
     }
 
     #[inline]
@@ -48,5 +102,32 @@ impl Token {
     pub fn null_token() -> Self {
         Self::new(TokenKind::Null, Some("null".to_string()))
     }
+
+    /// Attach the location of this token in the original input
+    #[inline]
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Text of the token as it would appear in JSON output
+    pub fn text(&self) -> String {
+        match (self.kind, &self.value) {
+            (TokenKind::String, Some(value)) => serde_json::to_string(value).unwrap_or_default(),
+            (_, Some(value)) => value.clone(),
+            _ => String::new(),
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_escapes_strings() {
+        let token = Token::new(TokenKind::String, Some("say \"hi\"\\\n".to_string()));
+        assert_eq!(token.text(), r#""say \"hi\"\\\n""#);
+        assert_eq!(Token::null_token().text(), "null");
+    }
+}
@@ -1,82 +1,214 @@
-use crate::token::{Token, TokenKind};
+use crate::report::{Repair, RepairKind};
+use crate::token::{Span, Token, TokenKind};
 
 /// Fixes mismatched brackets and braces in token stream
 pub struct JSONTokenFixer;
 
+/// Closers still expected by a token stream
+/// Kept between calls so a stream arriving in chunks is balanced incrementally
+#[derive(Debug, Clone, Default)]
+pub struct BracketStack {
+    stack: Vec<TokenKind>,
+    /// A closer for the next bracket out where the innermost array expects an element,
+    /// held back with the tokens after it until they show whether a member follows
+    pending: Option<(Token, Vec<Token>)>,
+}
+
 impl JSONTokenFixer {
     /// Fix tokens by ensuring all opening brackets/braces have matching closing ones
     /// Optimized to reduce allocations and cloning
     pub fn fix_tokens(tokens: Vec<Token>) -> Vec<Token> {
+        Self::fix_tokens_with_repairs(tokens, &mut Vec::new())
+    }
+
+    /// Fix tokens, recording every inserted or dropped bracket
+    pub fn fix_tokens_with_repairs(tokens: Vec<Token>, repairs: &mut Vec<Repair>) -> Vec<Token> {
         let mut fixed = Vec::with_capacity(tokens.len() + 16); // Extra space for potential fixes
-        let mut stack: Vec<TokenKind> = Vec::with_capacity(32); // Stack for tracking open brackets
+        let mut stack = BracketStack::new();
         let mut eof_token: Option<Token> = None;
 
         for token in tokens {
-            match token.kind {
-                TokenKind::Eof => {
-                    // Save EOF token to add at the very end
-                    eof_token = Some(token);
-                }
-                TokenKind::LBrace | TokenKind::LBracket => {
-                    let expected_close = if token.kind == TokenKind::LBrace {
-                        TokenKind::RBrace
+            if token.kind == TokenKind::Eof {
+                // Save EOF token to add at the very end
+                eof_token = Some(token);
+            } else {
+                stack.push(token, &mut fixed, repairs);
+            }
+        }
+
+        // Close any remaining open brackets at the end of input
+        let end = eof_token.as_ref().map_or(Span::default(), |eof| eof.span.empty());
+        stack.close_all(end, &mut fixed, repairs);
+
+        // Add EOF token at the very end if it exists
+        if let Some(eof) = eof_token {
+            fixed.push(eof);
+        }
+
+        fixed
+    }
+}
+
+impl BracketStack {
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::with_capacity(32),
+            pending: None,
+        }
+    }
+
+    /// Number of brackets still open
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Append one token to `fixed`, inserting or dropping closers to keep it balanced
+    pub fn push(&mut self, token: Token, fixed: &mut Vec<Token>, repairs: &mut Vec<Repair>) {
+        if let Some((closer, mut after)) = self.pending.take() {
+            after.push(token);
+            let kinds: Vec<TokenKind> = after.iter().map(|t| t.kind).collect();
+            if matches!(kinds[..], [TokenKind::Comma] | [TokenKind::Comma, TokenKind::String]) {
+                self.pending = Some((closer, after));
+            } else {
+                self.resolve_pending(closer, after, fixed, repairs);
+            }
+            return;
+        }
+        match token.kind {
+            TokenKind::LBrace | TokenKind::LBracket => {
+                let expected_close = if token.kind == TokenKind::LBrace {
+                    TokenKind::RBrace
+                } else {
+                    TokenKind::RBracket
+                };
+                fixed.push(token);
+                self.stack.push(expected_close);
+            }
+            TokenKind::RBrace | TokenKind::RBracket => {
+                if let Some(&expected) = self.stack.last() {
+                    if token.kind == expected {
+                        // Matching closing bracket
+                        self.stack.pop();
+                        fixed.push(token);
+                    } else if self.expects_element(fixed) && self.outer() == Some(token.kind) {
+                        // Whether it ends the array depends on what follows
+                        self.pending = Some((token, Vec::new()));
+                    } else if self.outer() != Some(token.kind) {
+                        // It closes nothing, so the parser skips it like garbage
+                        fixed.push(stray(token, repairs));
                     } else {
-                        TokenKind::RBracket
-                    };
-                    fixed.push(token);
-                    stack.push(expected_close);
-                }
-                TokenKind::RBrace | TokenKind::RBracket => {
-                    if let Some(&expected) = stack.last() {
-                        if token.kind == expected {
-                            // Matching closing bracket
-                            stack.pop();
+                        // Mismatched closing bracket - insert correct one first
+                        let closer = create_closing_token(expected).with_span(token.span.empty());
+                        repairs.push(closer_repair(RepairKind::InsertedCloser, &closer));
+                        fixed.push(closer);
+                        self.stack.pop();
+
+                        // Check if current token matches new top of stack
+                        if self.stack.last() == Some(&token.kind) {
+                            self.stack.pop();
                             fixed.push(token);
                         } else {
-                            // Mismatched closing bracket - insert correct one first
-                            fixed.push(Self::create_closing_token(expected));
-                            stack.pop();
-
-                            // Check if current token matches new top of stack
-                            if let Some(&new_expected) = stack.last() {
-                                if token.kind == new_expected {
-                                    stack.pop();
-                                    fixed.push(token);
-                                }
-                            }
+                            repairs.push(closer_repair(RepairKind::DroppedCloser, &token));
                         }
-                    } else {
-                        // Closing bracket without opening - skip it
-                        // (or we could add it anyway, depending on desired behavior)
                     }
+                } else {
+                    // Closing bracket without opening - skip it
+                    repairs.push(closer_repair(RepairKind::DroppedCloser, &token));
                 }
-                _ => {
-                    fixed.push(token);
-                }
+            }
+            _ => {
+                fixed.push(token);
             }
         }
+    }
 
-        // Close any remaining open brackets
-        while let Some(expected) = stack.pop() {
-            fixed.push(Self::create_closing_token(expected));
+    /// Closer of the next bracket out, the one a closer that does not match the innermost
+    /// bracket has to match to end a container whose own closer went missing
+    fn outer(&self) -> Option<TokenKind> {
+        self.stack.len().checked_sub(2).map(|i| self.stack[i])
+    }
+
+    /// Whether the innermost array expects an element, where a closer is garbage unless a
+    /// member of the object around the array follows it
+    fn expects_element(&self, fixed: &[Token]) -> bool {
+        self.stack.last() == Some(&TokenKind::RBracket)
+            && matches!(
+                fixed.last().map(|t| t.kind),
+                Some(TokenKind::LBracket | TokenKind::Comma)
+            )
+    }
+
+    /// Settle a held closer now that the tokens after it are known: before `, "key":` it is
+    /// the array's closer mistyped as the object's, as in `{"a": [1, }, "b": 2}`; at the end
+    /// of the input it closes the object after the array's missing closer, as in
+    /// `{"a": [}`; before anything else it is garbage, as in `{"a": [1, }, 2]}`
+    fn resolve_pending(
+        &mut self,
+        closer: Token,
+        after: Vec<Token>,
+        fixed: &mut Vec<Token>,
+        repairs: &mut Vec<Repair>,
+    ) {
+        let kinds: Vec<TokenKind> = after.iter().map(|t| t.kind).collect();
+        if kinds.is_empty() || kinds == [TokenKind::Comma, TokenKind::String, TokenKind::Colon] {
+            if let Some(expected) = self.stack.pop() {
+                let end = create_closing_token(expected).with_span(closer.span.empty());
+                repairs.push(closer_repair(RepairKind::InsertedCloser, &end));
+                fixed.push(end);
+            }
+            if kinds.is_empty() {
+                self.stack.pop();
+                fixed.push(closer);
+            } else {
+                repairs.push(closer_repair(RepairKind::DroppedCloser, &closer));
+            }
+        } else {
+            fixed.push(stray(closer, repairs));
+        }
+        for token in after {
+            self.push(token, fixed, repairs);
         }
+    }
 
-        // Add EOF token at the very end if it exists
-        if let Some(eof) = eof_token {
-            fixed.push(eof);
+    /// Close every open bracket with a zero-width closer at `end`
+    pub fn close_all(&mut self, end: Span, fixed: &mut Vec<Token>, repairs: &mut Vec<Repair>) {
+        if let Some((closer, after)) = self.pending.take() {
+            self.resolve_pending(closer, after, fixed, repairs);
+        }
+        while let Some(expected) = self.stack.pop() {
+            let closer = create_closing_token(expected).with_span(end);
+            repairs.push(closer_repair(RepairKind::InsertedCloser, &closer));
+            fixed.push(closer);
         }
+    }
+}
 
-        fixed
+/// Report a closer that closes nothing and turn it into a token the parser skips
+/// Garbage in an array still separates the elements around it, as in `[1, }, 2]`
+fn stray(token: Token, repairs: &mut Vec<Repair>) -> Token {
+    repairs.push(closer_repair(RepairKind::DroppedCloser, &token));
+    let span = token.span;
+    Token::new(TokenKind::Stray, token.value).with_span(span)
+}
+
+/// Create a closing bracket/brace token
+#[inline]
+fn create_closing_token(kind: TokenKind) -> Token {
+    match kind {
+        TokenKind::RBrace => Token::new(TokenKind::RBrace, Some("}".to_string())),
+        TokenKind::RBracket => Token::new(TokenKind::RBracket, Some("]".to_string())),
+        _ => Token::new_simple(TokenKind::Unknown),
     }
+}
 
-    /// Create a closing bracket/brace token
-    #[inline]
-    fn create_closing_token(kind: TokenKind) -> Token {
-        match kind {
-            TokenKind::RBrace => Token::new(TokenKind::RBrace, Some("}".to_string())),
-            TokenKind::RBracket => Token::new(TokenKind::RBracket, Some("]".to_string())),
-            _ => Token::new_simple(TokenKind::Unknown),
-        }
+/// Build a repair entry for an inserted or dropped closing token
+#[inline]
+fn closer_repair(kind: RepairKind, token: &Token) -> Repair {
+    let text = token.text();
+    match kind {
+        RepairKind::InsertedCloser => Repair::new(kind, token.span, "", text),
+        _ => Repair::new(kind, token.span, text, ""),
     }
 }
 
@@ -144,5 +276,101 @@ mod tests {
             .count();
         assert_eq!(closing_count, 3);
     }
-}
 
+    #[test]
+    fn test_stray_closers_are_dropped() {
+        let fix = |tokens: Vec<Token>| {
+            let mut repairs = Vec::new();
+            let fixed = JSONTokenFixer::fix_tokens_with_repairs(tokens, &mut repairs);
+            let kinds: Vec<_> = fixed.iter().map(|t| t.kind).collect();
+            (kinds, repairs.iter().map(|r| r.kind).collect::<Vec<_>>())
+        };
+        let token = |kind, text: &str| Token::new(kind, Some(text.to_string()));
+
+        // Where the array expects an element, a `}` followed by more elements closes nothing
+        let tokens = vec![
+            token(TokenKind::LBrace, "{"),
+            token(TokenKind::LBracket, "["),
+            token(TokenKind::Comma, ","),
+            token(TokenKind::RBrace, "}"),
+            token(TokenKind::Comma, ","),
+            token(TokenKind::Number, "1"),
+            Token::new_simple(TokenKind::Eof),
+        ];
+        let (kinds, repairs) = fix(tokens);
+        assert_eq!(kinds, [
+            TokenKind::LBrace,
+            TokenKind::LBracket,
+            TokenKind::Comma,
+            TokenKind::Stray,
+            TokenKind::Comma,
+            TokenKind::Number,
+            TokenKind::RBracket,
+            TokenKind::RBrace,
+            TokenKind::Eof,
+        ]);
+        assert_eq!(repairs, [
+            RepairKind::DroppedCloser,
+            RepairKind::InsertedCloser,
+            RepairKind::InsertedCloser,
+        ]);
+
+        // At the end of the input it closes the object once the array is closed
+        let tokens = vec![
+            token(TokenKind::LBrace, "{"),
+            token(TokenKind::LBracket, "["),
+            token(TokenKind::RBrace, "}"),
+            Token::new_simple(TokenKind::Eof),
+        ];
+        let (kinds, repairs) = fix(tokens);
+        assert_eq!(kinds, [
+            TokenKind::LBrace,
+            TokenKind::LBracket,
+            TokenKind::RBracket,
+            TokenKind::RBrace,
+            TokenKind::Eof,
+        ]);
+        assert_eq!(repairs, [RepairKind::InsertedCloser]);
+
+        // Nor does one whose opener is not the next bracket out
+        let tokens = vec![
+            token(TokenKind::LBracket, "["),
+            token(TokenKind::Number, "1"),
+            token(TokenKind::RBrace, "}"),
+            Token::new_simple(TokenKind::Eof),
+        ];
+        let (kinds, repairs) = fix(tokens);
+        assert_eq!(kinds, [
+            TokenKind::LBracket,
+            TokenKind::Number,
+            TokenKind::Stray,
+            TokenKind::RBracket,
+            TokenKind::Eof,
+        ]);
+        assert_eq!(repairs, [RepairKind::DroppedCloser, RepairKind::InsertedCloser]);
+    }
+
+    #[test]
+    fn test_fix_records_repairs() {
+        let tokens = vec![
+            Token::new(TokenKind::RBracket, Some("]".to_string())).with_span(Span::new(0, 1, 1, 1)),
+            Token::new(TokenKind::LBracket, Some("[".to_string())).with_span(Span::new(1, 2, 1, 2)),
+            Token::new(TokenKind::Number, Some("1".to_string())).with_span(Span::new(2, 3, 1, 3)),
+            Token::new_simple(TokenKind::Eof).with_span(Span::new(3, 3, 1, 4)),
+        ];
+
+        let mut repairs = Vec::new();
+        let fixed = JSONTokenFixer::fix_tokens_with_repairs(tokens, &mut repairs);
+
+        // Synthesized closer gets a zero-width span at the insertion point
+        assert_eq!(fixed[2].kind, TokenKind::RBracket);
+        assert_eq!(fixed[2].span, Span::new(3, 3, 1, 4));
+        assert_eq!(
+            repairs,
+            vec![
+                Repair::new(RepairKind::DroppedCloser, Span::new(0, 1, 1, 1), "]", ""),
+                Repair::new(RepairKind::InsertedCloser, Span::new(3, 3, 1, 4), "", "]"),
+            ]
+        );
+    }
+}
@@ -1,36 +1,129 @@
-use crate::token::{Token, TokenKind};
-use once_cell::sync::Lazy;
-use regex::{Regex, RegexBuilder};
+use crate::number::{is_valid_number, repair_number};
+use crate::options::{CorrectorOptions, NonFiniteNumbers, RepairAction};
+use crate::report::{Repair, RepairKind};
+use crate::token::{Span, Token, TokenKind};
 
-static NUM_PATTERN: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^-?\d+(\.\d+)?([eE][+-]?\d+)?$").unwrap()
-});
+/// Whether the text is a bare identifier: a letter or `_` followed by word characters
+fn is_identifier(raw: &str) -> bool {
+    let mut chars = raw.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Tracks line and column while the tokenizer moves forward through the input
+struct LineTracker {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl LineTracker {
+    fn new() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Build a span for `start..end`; offsets must be requested in increasing order
+    #[inline]
+    fn span(&mut self, bytes: &[u8], start: usize, end: usize) -> Span {
+        for &b in &bytes[self.offset..start] {
+            if b == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if b & 0xC0 != 0x80 {
+                // Count characters, not UTF-8 continuation bytes
+                self.column += 1;
+            }
+        }
+        self.offset = start;
+        Span::new(start, end, self.line, self.column)
+    }
+}
+
+/// Result of decoding one UTF-8 character from raw bytes
+enum Utf8Char {
+    /// A valid character and its length in bytes
+    Valid(char, usize),
+    /// An invalid sequence of the given length
+    Invalid(usize),
+    /// A sequence cut off by the end of input
+    Truncated,
+}
 
-static ID_PATTERN: Lazy<Regex> = Lazy::new(|| {
-    RegexBuilder::new(r"^[A-Za-z_]\w*$")
-        .case_insensitive(false)
-        .build()
-        .unwrap()
-});
+/// Decode the UTF-8 character starting at `i`
+#[inline]
+fn decode_char(bytes: &[u8], i: usize) -> Utf8Char {
+    let end = (i + 4).min(bytes.len());
+    let valid = match std::str::from_utf8(&bytes[i..end]) {
+        Ok(valid) => valid,
+        Err(e) if e.valid_up_to() > 0 => {
+            std::str::from_utf8(&bytes[i..i + e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(e) => {
+            return match e.error_len() {
+                Some(len) => Utf8Char::Invalid(len),
+                None => Utf8Char::Truncated,
+            }
+        }
+    };
+    match valid.chars().next() {
+        Some(c) => Utf8Char::Valid(c, c.len_utf8()),
+        None => Utf8Char::Truncated,
+    }
+}
 
 /// High-performance JSON tokenizer optimized for large inputs
 pub struct JSONTokenizer {
     // Reusable buffer for string building
     string_buffer: String,
+    options: CorrectorOptions,
 }
 
 impl JSONTokenizer {
     pub fn new() -> Self {
+        Self::with_options(CorrectorOptions::default())
+    }
+
+    /// Create a tokenizer that applies the given repair policy to literals
+    pub fn with_options(options: CorrectorOptions) -> Self {
         Self {
             string_buffer: String::with_capacity(256),
+            options,
         }
     }
 
     /// Tokenize JSON input with optimized performance for large strings
     pub fn tokenize(&mut self, input: &str) -> Vec<Token> {
-        let bytes = input.as_bytes();
+        self.tokenize_with_repairs(input, &mut Vec::new())
+    }
+
+    /// Tokenize JSON input, recording every repair made along the way
+    pub fn tokenize_with_repairs(&mut self, input: &str, repairs: &mut Vec<Repair>) -> Vec<Token> {
+        self.tokenize_bytes(input.as_bytes(), repairs)
+    }
+
+    /// Tokenize raw UTF-8 bytes, recording every repair made along the way
+    /// Invalid sequences become U+FFFD and a sequence cut off at the end of input is dropped
+    pub fn tokenize_bytes(&mut self, bytes: &[u8], repairs: &mut Vec<Repair>) -> Vec<Token> {
+        self.tokenize_counted(bytes, 0, repairs)
+    }
+
+    /// Tokenize bytes that follow `counted` tokens already taken from the same input,
+    /// so the token limit applies to the input as a whole
+    pub(crate) fn tokenize_counted(
+        &mut self,
+        bytes: &[u8],
+        counted: usize,
+        repairs: &mut Vec<Repair>,
+    ) -> Vec<Token> {
         let length = bytes.len();
         let mut tokens = Vec::with_capacity(length / 4); // Estimate: avg 4 bytes per token
+        let mut lines = LineTracker::new();
         let mut i = 0;
 
         while i < length {
@@ -42,131 +135,482 @@ impl JSONTokenizer {
                 continue;
             }
 
+            // Comments are not tokens, so neither they nor whitespace count against the limit
+            let comment = matches!(c, '/' | '#') && Self::comment_end(bytes, i).is_some();
+            let limit = self.options.token_limit();
+            if !comment && limit.is_some_and(|max| counted + tokens.len() >= max) {
+                let span = lines.span(bytes, i, length);
+                repairs.push(Repair::new(RepairKind::TokenLimited, span, "", ""));
+                break;
+            }
+
             match c {
-                '{' => {
-                    tokens.push(Token::new(TokenKind::LBrace, Some("{".to_string())));
-                    i += 1;
-                }
-                '}' => {
-                    tokens.push(Token::new(TokenKind::RBrace, Some("}".to_string())));
+                '{' | '}' | '[' | ']' | ':' | ',' => {
+                    let kind = match c {
+                        '{' => TokenKind::LBrace,
+                        '}' => TokenKind::RBrace,
+                        '[' => TokenKind::LBracket,
+                        ']' => TokenKind::RBracket,
+                        ':' => TokenKind::Colon,
+                        _ => TokenKind::Comma,
+                    };
+                    let span = lines.span(bytes, i, i + 1);
+                    tokens.push(Token::new(kind, Some(c.to_string())).with_span(span));
                     i += 1;
                 }
-                '[' => {
-                    tokens.push(Token::new(TokenKind::LBracket, Some("[".to_string())));
-                    i += 1;
-                }
-                ']' => {
-                    tokens.push(Token::new(TokenKind::RBracket, Some("]".to_string())));
-                    i += 1;
+                '/' | '#' if Self::comment_end(bytes, i).is_some() => {
+                    let end = Self::comment_end(bytes, i).unwrap_or(length);
+                    let span = lines.span(bytes, i, end);
+                    let original = String::from_utf8_lossy(&bytes[i..end]);
+                    repairs.push(Repair::new(RepairKind::RemovedComment, span, original, ""));
+                    i = end;
                 }
-                ':' => {
-                    tokens.push(Token::new(TokenKind::Colon, Some(":".to_string())));
-                    i += 1;
-                }
-                ',' => {
-                    tokens.push(Token::new(TokenKind::Comma, Some(",".to_string())));
-                    i += 1;
-                }
-                '"' => {
-                    i += 1;
-                    let (string_value, new_pos) = self.parse_string(input, i, length);
-                    tokens.push(Token::new(TokenKind::String, Some(string_value)));
+                '"' | '\'' | '`' => {
+                    let start = i;
+                    let quote = bytes[i];
+                    let span = lines.span(bytes, start, start);
+                    let (string_value, new_pos, closed) =
+                        self.parse_string(bytes, i + 1, quote, &mut lines, repairs);
+                    let span = Span { end: new_pos, ..span };
+                    let mut token = Token::new(TokenKind::String, Some(string_value)).with_span(span);
+                    self.limit_string(&mut token, repairs);
+                    tokens.push(token);
+                    if quote != b'"' {
+                        // Single-quoted (Python, JavaScript) and backtick (template) strings
+                        let original = c.to_string();
+                        repairs.push(Repair::new(RepairKind::RequotedString, span, original, "\""));
+                    }
+                    if !closed {
+                        let end = lines.span(bytes, length, length);
+                        repairs.push(Repair::new(RepairKind::ClosedString, end, "", "\""));
+                    }
                     i = new_pos;
                 }
-                _ => {
-                    if c.is_ascii_alphabetic() || c == '-' || c.is_ascii_digit() {
+                _ => match decode_char(bytes, i) {
+                    Utf8Char::Valid(ch, _) if Self::is_literal_start(ch) => {
                         let start = i;
-                        while i < length {
-                            let ch = bytes[i] as char;
-                            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '+' || ch == '.' || ch == 'e' || ch == 'E' {
-                                i += 1;
-                            } else {
-                                break;
+                        i = Self::scan_literal(bytes, i);
+                        let raw_val = String::from_utf8_lossy(&bytes[start..i]);
+                        let span = lines.span(bytes, start, i);
+                        match self.correct_literal(&raw_val, span) {
+                            (token, None) => tokens.push(token),
+                            (token, Some(repair)) => {
+                                let counted = counted + tokens.len();
+                                if !self.apply_repair(token, repair, counted, &mut tokens, repairs)
+                                {
+                                    let span = Span { end: length, ..span };
+                                    let kind = RepairKind::TokenLimited;
+                                    repairs.push(Repair::new(kind, span, "", ""));
+                                    break;
+                                }
                             }
                         }
-                        let raw_val = &input[start..i];
-                        tokens.push(self.correct_literal(raw_val));
-                    } else {
-                        i += 1;
                     }
-                }
+                    Utf8Char::Valid(ch, len) => {
+                        let span = lines.span(bytes, i, i + len);
+                        repairs.push(Repair::new(RepairKind::DroppedToken, span, ch, ""));
+                        i += len;
+                    }
+                    Utf8Char::Invalid(len) => {
+                        let span = lines.span(bytes, i, i + len);
+                        let original = String::from_utf8_lossy(&bytes[i..i + len]);
+                        repairs.push(Repair::new(RepairKind::InvalidUtf8, span, original, ""));
+                        i += len;
+                    }
+                    Utf8Char::Truncated => {
+                        let span = lines.span(bytes, i, length);
+                        let original = String::from_utf8_lossy(&bytes[i..]);
+                        repairs.push(Repair::new(RepairKind::TruncatedUtf8, span, original, ""));
+                        i = length;
+                    }
+                },
             }
         }
 
-        tokens.push(Token::new_simple(TokenKind::Eof));
+        let eof = lines.span(bytes, length, length);
+        tokens.push(Token::new_simple(TokenKind::Eof).with_span(eof));
         tokens
     }
 
+    /// Decode the contents of a string whose opening quote came before `bytes`
+    /// Returns the text, the position after the closing quote (or the end) and whether it closed.
+    /// Spans in `repairs` are relative to the start of `bytes`
+    pub(crate) fn string_contents(
+        &mut self,
+        bytes: &[u8],
+        quote: u8,
+        repairs: &mut Vec<Repair>,
+    ) -> (String, usize, bool) {
+        self.parse_string(bytes, 0, quote, &mut LineTracker::new(), repairs)
+    }
+
+    /// Cut a string token that is longer than the configured limit
+    pub(crate) fn limit_string(&self, token: &mut Token, repairs: &mut Vec<Repair>) {
+        let (Some(limit), Some(value)) = (self.options.string_length_limit(), token.value.as_mut())
+        else {
+            return;
+        };
+        if value.len() > limit {
+            let end = (0..=limit).rev().find(|&i| value.is_char_boundary(i)).unwrap_or(0);
+            value.truncate(end);
+            repairs.push(Repair::new(RepairKind::StringLimited, token.span, "", ""));
+        }
+    }
+
+    /// If a comment starts at `start`, return the position after it
+    /// Handles `// line`, `# line` and `/* block */`; an unterminated block runs to the end
+    #[inline]
+    fn comment_end(bytes: &[u8], start: usize) -> Option<usize> {
+        let line_end = |from: usize| {
+            bytes[from..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(bytes.len(), |p| from + p)
+        };
+        match (bytes[start], bytes.get(start + 1)) {
+            (b'#', _) => Some(line_end(start + 1)),
+            (b'/', Some(b'/')) => Some(line_end(start + 2)),
+            (b'/', Some(b'*')) => {
+                let end = bytes[start + 2..]
+                    .windows(2)
+                    .position(|w| w == b"*/")
+                    .map_or(bytes.len(), |p| start + 2 + p + 2);
+                Some(end)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether a character can start an unquoted literal (number, keyword or identifier)
+    #[inline]
+    fn is_literal_start(c: char) -> bool {
+        c.is_alphabetic() || c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | '_')
+    }
+
+    /// Find the end of the unquoted literal starting at `start`
+    #[inline]
+    fn scan_literal(bytes: &[u8], start: usize) -> usize {
+        let mut i = start;
+        while i < bytes.len() {
+            let ch = bytes[i] as char;
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '+' || ch == '.' || ch == '_' {
+                i += 1;
+            } else if ch.is_ascii() {
+                break;
+            } else {
+                match decode_char(bytes, i) {
+                    Utf8Char::Valid(c, len) if c.is_alphanumeric() => i += len,
+                    _ => break,
+                }
+            }
+        }
+        i
+    }
+
     /// Optimized string parsing with escape sequence handling
+    /// Returns the string value, the position after it and whether the closing quote was found
     #[inline]
-    fn parse_string(&mut self, input: &str, start: usize, length: usize) -> (String, usize) {
+    fn parse_string(
+        &mut self,
+        bytes: &[u8],
+        start: usize,
+        quote: u8,
+        lines: &mut LineTracker,
+        repairs: &mut Vec<Repair>,
+    ) -> (String, usize, bool) {
         self.string_buffer.clear();
-        let bytes = input.as_bytes();
+        let length = bytes.len();
         let mut i = start;
 
         while i < length {
-            let c = bytes[i] as char;
-            
-            if c == '"' {
-                i += 1;
-                break;
-            } else if c == '\\' {
-                if i + 1 < length {
-                    self.string_buffer.push(c);
-                    self.string_buffer.push(bytes[i + 1] as char);
-                    i += 2;
-                } else {
-                    i += 1;
-                    break;
+            match bytes[i] {
+                b if b == quote => return (self.string_buffer.clone(), i + 1, true),
+                b'\\' => i = self.parse_escape(bytes, i, quote, lines, repairs),
+                _ => {
+                    // Copy the whole run up to the next quote or backslash at once
+                    let run_end = bytes[i..]
+                        .iter()
+                        .position(|&b| b == quote || b == b'\\')
+                        .map_or(length, |p| i + p);
+                    self.push_utf8(bytes, i, run_end, lines, repairs);
+                    i = run_end;
                 }
-            } else {
-                self.string_buffer.push(c);
-                i += 1;
             }
         }
 
-        (self.string_buffer.clone(), i)
+        (self.string_buffer.clone(), i, false)
+    }
+
+    /// Append `bytes[start..end]` to the string buffer, repairing invalid UTF-8
+    fn push_utf8(
+        &mut self,
+        bytes: &[u8],
+        start: usize,
+        end: usize,
+        lines: &mut LineTracker,
+        repairs: &mut Vec<Repair>,
+    ) {
+        let mut i = start;
+        while i < end {
+            match std::str::from_utf8(&bytes[i..end]) {
+                Ok(valid) => {
+                    self.string_buffer.push_str(valid);
+                    return;
+                }
+                Err(e) => {
+                    let valid_end = i + e.valid_up_to();
+                    // The prefix up to `valid_up_to` is guaranteed to be valid UTF-8
+                    let valid = std::str::from_utf8(&bytes[i..valid_end]).unwrap_or_default();
+                    self.string_buffer.push_str(valid);
+                    let bad_end = e.error_len().map_or(end, |len| valid_end + len);
+                    let span = lines.span(bytes, valid_end, bad_end);
+                    let original = String::from_utf8_lossy(&bytes[valid_end..bad_end]);
+                    if e.error_len().is_none() {
+                        // Multi-byte sequence cut off at the end of input
+                        repairs.push(Repair::new(RepairKind::TruncatedUtf8, span, original, ""));
+                        return;
+                    }
+                    repairs.push(Repair::new(RepairKind::InvalidUtf8, span, original, "\u{fffd}"));
+                    self.string_buffer.push(char::REPLACEMENT_CHARACTER);
+                    i = bad_end;
+                }
+            }
+        }
+    }
+
+    /// Decode the escape sequence whose backslash is at `start` into the string buffer
+    /// Invalid and truncated escapes are repaired; returns the position after the sequence
+    fn parse_escape(
+        &mut self,
+        bytes: &[u8],
+        start: usize,
+        quote: u8,
+        lines: &mut LineTracker,
+        repairs: &mut Vec<Repair>,
+    ) -> usize {
+        let Some(&escaped) = bytes.get(start + 1) else {
+            // Lone backslash at the end of input
+            let span = lines.span(bytes, start, start + 1);
+            repairs.push(Repair::new(RepairKind::TruncatedEscape, span, "\\", ""));
+            return start + 1;
+        };
+
+        let decoded = match escaped {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => return self.parse_unicode_escape(bytes, start, lines, repairs),
+            // Escaped quote inside a single-quoted or backtick string
+            q if q == quote => q as char,
+            _ => {
+                // Not a JSON escape: decode `\xHH` as a code point, otherwise keep the character
+                let (decoded, end) = match Self::hex_value(bytes, start + 2, 2) {
+                    Some(code) if escaped == b'x' => (char::from(code as u8), start + 4),
+                    _ => match decode_char(bytes, start + 1) {
+                        Utf8Char::Valid(c, len) => (c, start + 1 + len),
+                        // Drop the backslash and leave malformed bytes to the string parser
+                        _ => {
+                            let span = lines.span(bytes, start, start + 1);
+                            repairs.push(Repair::new(RepairKind::InvalidEscape, span, "\\", ""));
+                            return start + 1;
+                        }
+                    },
+                };
+                let span = lines.span(bytes, start, end);
+                let original = String::from_utf8_lossy(&bytes[start..end]);
+                repairs.push(Repair::new(RepairKind::InvalidEscape, span, original, decoded));
+                self.string_buffer.push(decoded);
+                return end;
+            }
+        };
+
+        self.string_buffer.push(decoded);
+        start + 2
+    }
+
+    /// Decode a `\uXXXX` escape, combining surrogate pairs
+    /// Lone surrogates become U+FFFD and incomplete escapes are dropped
+    fn parse_unicode_escape(
+        &mut self,
+        bytes: &[u8],
+        start: usize,
+        lines: &mut LineTracker,
+        repairs: &mut Vec<Repair>,
+    ) -> usize {
+        let Some(code) = Self::hex_value(bytes, start + 2, 4) else {
+            let digits = bytes[start + 2..]
+                .iter()
+                .take(4)
+                .take_while(|b| b.is_ascii_hexdigit())
+                .count();
+            let end = start + 2 + digits;
+            let kind = if end == bytes.len() {
+                RepairKind::TruncatedEscape
+            } else {
+                RepairKind::InvalidEscape
+            };
+            let span = lines.span(bytes, start, end);
+            repairs.push(Repair::new(kind, span, String::from_utf8_lossy(&bytes[start..end]), ""));
+            return end;
+        };
+
+        let mut end = start + 6;
+        let decoded = match code {
+            0xD800..=0xDBFF => {
+                // High surrogate must be followed by an escaped low surrogate
+                let low = match (bytes.get(end), bytes.get(end + 1)) {
+                    (Some(b'\\'), Some(b'u')) => Self::hex_value(bytes, end + 2, 4),
+                    _ => None,
+                };
+                match low {
+                    Some(low @ 0xDC00..=0xDFFF) => {
+                        end += 6;
+                        char::from_u32(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00))
+                    }
+                    _ => None,
+                }
+            }
+            _ => char::from_u32(code),
+        };
+
+        let decoded = decoded.unwrap_or_else(|| {
+            let span = lines.span(bytes, start, end);
+            repairs.push(Repair::new(
+                RepairKind::LoneSurrogate,
+                span,
+                String::from_utf8_lossy(&bytes[start..end]),
+                "\u{fffd}",
+            ));
+            char::REPLACEMENT_CHARACTER
+        });
+        self.string_buffer.push(decoded);
+        end
+    }
+
+    /// Read exactly `count` hex digits at `start`
+    #[inline]
+    fn hex_value(bytes: &[u8], start: usize, count: usize) -> Option<u32> {
+        let digits = bytes.get(start..start + count)?;
+        digits.iter().try_fold(0u32, |acc, &b| {
+            (b as char).to_digit(16).map(|d| acc * 16 + d)
+        })
     }
 
     /// Correct and identify literal tokens (true, false, null, numbers, identifiers)
+    /// Returns the token and the repair needed to produce it, if any
     #[inline]
-    fn correct_literal(&self, raw: &str) -> Token {
+    fn correct_literal(&self, raw: &str, span: Span) -> (Token, Option<Repair>) {
         let len = raw.len();
 
+        // Python and JavaScript spellings of null
+        if raw == "None" || raw == "undefined" {
+            let repair = Repair::new(RepairKind::ConvertedLiteral, span, raw, "null");
+            return (Token::null_token().with_span(span), Some(repair));
+        }
+
+        // JSON has no representation for non-finite numbers
+        if matches!(raw, "NaN" | "Infinity" | "-Infinity" | "+Infinity") {
+            let token = match self.options.non_finite_policy() {
+                NonFiniteNumbers::String => Token::new(TokenKind::String, Some(raw.to_string())),
+                NonFiniteNumbers::Null | NonFiniteNumbers::Error => Token::null_token(),
+            };
+            let repair = Repair::new(RepairKind::NonFiniteNumber, span, raw, token.text());
+            return (token.with_span(span), Some(repair));
+        }
+
         // Fast path for boolean and null literals
         if len <= 5 {
             let first = raw.as_bytes()[0] as char;
-            match first.to_ascii_lowercase() {
-                't' => {
-                    if self.match_literal(raw, "true") {
-                        return Token::true_token();
-                    }
-                }
-                'f' => {
-                    if self.match_literal(raw, "false") {
-                        return Token::false_token();
-                    }
-                }
-                'n' => {
-                    if self.match_literal(raw, "null") {
-                        return Token::null_token();
-                    }
-                }
-                _ => {}
+            let literal = match first.to_ascii_lowercase() {
+                't' if self.match_literal(raw, "true") => Some(Token::true_token()),
+                'f' if self.match_literal(raw, "false") => Some(Token::false_token()),
+                'n' if self.match_literal(raw, "null") => Some(Token::null_token()),
+                _ => None,
+            };
+            if let Some(token) = literal {
+                let full = token.text();
+                let repair = (raw != full)
+                    .then(|| Repair::new(RepairKind::CompletedLiteral, span, raw, full));
+                return (token.with_span(span), repair);
             }
         }
 
         // Check if it's a number
-        if NUM_PATTERN.is_match(raw) {
-            return Token::new(TokenKind::Number, Some(raw.to_string()));
+        if is_valid_number(raw) {
+            let token = Token::new(TokenKind::Number, Some(raw.to_string())).with_span(span);
+            return (token, None);
+        }
+
+        // Malformed numbers such as `01`, `.5` or `0x1F` are rewritten into valid JSON
+        if matches!(raw.as_bytes()[0], b'0'..=b'9' | b'-' | b'+' | b'.') {
+            if let Some(number) = repair_number(raw) {
+                let repair = Repair::new(RepairKind::RepairedNumber, span, raw, number.as_str());
+                let token = Token::new(TokenKind::Number, Some(number)).with_span(span);
+                return (token, Some(repair));
+            }
         }
 
         // Check if it's a valid identifier (treat as string)
-        if ID_PATTERN.is_match(raw) {
-            return Token::new(TokenKind::String, Some(raw.to_string()));
+        if is_identifier(raw) {
+            let repair = Repair::new(
+                RepairKind::QuotedIdentifier,
+                span,
+                raw,
+                format!("\"{}\"", raw),
+            );
+            let token = Token::new(TokenKind::String, Some(raw.to_string())).with_span(span);
+            return (token, Some(repair));
         }
 
-        Token::new(TokenKind::Unknown, Some(raw.to_string()))
+        (Token::new(TokenKind::Unknown, Some(raw.to_string())).with_span(span), None)
+    }
+
+    /// Emit a repaired literal according to the action configured for its repair kind
+    /// Returns `false`, emitting nothing, when a substitute would take the tokens past the
+    /// limit, `counted` being the tokens taken before it
+    fn apply_repair(
+        &mut self,
+        token: Token,
+        repair: Repair,
+        counted: usize,
+        tokens: &mut Vec<Token>,
+        repairs: &mut Vec<Repair>,
+    ) -> bool {
+        match self.options.action(repair.kind).clone() {
+            RepairAction::Drop => {
+                // Marks the gap, so the parser removes the whole entry instead of filling it
+                tokens.push(Token::new_simple(TokenKind::Dropped).with_span(repair.span));
+                repairs.push(Repair {
+                    replacement: String::new(),
+                    ..repair
+                });
+            }
+            RepairAction::Substitute(value) => {
+                // Splice in the tokens of the substitute, all pointing at the original literal
+                let text = serde_json::to_string(&value).unwrap_or_else(|_| "null".to_string());
+                let mut limited = Vec::new();
+                let mut substitute = self.tokenize_counted(text.as_bytes(), counted, &mut limited);
+                if limited.iter().any(|r| r.kind == RepairKind::TokenLimited) {
+                    return false;
+                }
+                substitute.pop(); // Eof
+                tokens.extend(substitute.into_iter().map(|t| t.with_span(repair.span)));
+                repairs.push(Repair {
+                    replacement: text,
+                    ..repair
+                });
+            }
+            RepairAction::Repair | RepairAction::Error => {
+                tokens.push(token);
+                repairs.push(repair);
+            }
+        }
+        true
     }
 
     /// Match partial literal against full literal (case-insensitive)
@@ -241,6 +685,278 @@ mod tests {
         assert_eq!(tokens[0].kind, TokenKind::Null);
     }
 
+    #[test]
+    fn test_tokenizer_spans() {
+        let mut tokenizer = JSONTokenizer::new();
+        let tokens = tokenizer.tokenize("{\n  \"k\u{e9}y\": [tru,\n 12]\n}");
+
+        // tokens: { "kéy" : [ tru , 12 ] } EOF
+        assert_eq!(tokens[0].span, Span::new(0, 1, 1, 1));
+        assert_eq!(tokens[1].span, Span::new(4, 10, 2, 3));
+        assert_eq!(tokens[2].span, Span::new(10, 11, 2, 8));
+        assert_eq!(tokens[4].span, Span::new(13, 16, 2, 11));
+        assert_eq!(tokens[6].span, Span::new(19, 21, 3, 2));
+        assert_eq!(tokens[8].span, Span::new(23, 24, 4, 1));
+        assert_eq!(tokens[9].span, Span::new(24, 24, 4, 2));
+    }
+
+    #[test]
+    fn test_tokenizer_repairs() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        tokenizer.tokenize_with_repairs("{\"a\": tr, \"b\": \"x", &mut repairs);
+
+        assert_eq!(repairs.len(), 2);
+        assert_eq!(
+            repairs[0],
+            Repair::new(RepairKind::CompletedLiteral, Span::new(6, 8, 1, 7), "tr", "true")
+        );
+        assert_eq!(
+            repairs[1],
+            Repair::new(RepairKind::ClosedString, Span::new(17, 17, 1, 18), "", "\"")
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_escapes() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        let tokens = tokenizer.tokenize_with_repairs(
+            r#""a\"b\\c\/d\b\f\n\r\t\u00e9\ud83d\ude00""#,
+            &mut repairs,
+        );
+
+        assert_eq!(
+            tokens[0].value.as_deref(),
+            Some("a\"b\\c/d\u{8}\u{c}\n\r\t\u{e9}\u{1f600}")
+        );
+        assert!(repairs.is_empty());
+    }
+
+    #[test]
+    fn test_tokenizer_invalid_escapes() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        let tokens = tokenizer.tokenize_with_repairs(r#""\x41\q\ud800x\udc00\u12g""#, &mut repairs);
+
+        assert_eq!(tokens[0].value.as_deref(), Some("Aq\u{fffd}x\u{fffd}g"));
+        let kinds: Vec<RepairKind> = repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RepairKind::InvalidEscape,
+                RepairKind::InvalidEscape,
+                RepairKind::LoneSurrogate,
+                RepairKind::LoneSurrogate,
+                RepairKind::InvalidEscape,
+            ]
+        );
+        assert_eq!(repairs[0].original, "\\x41");
+        assert_eq!(repairs[0].replacement, "A");
+        assert_eq!(repairs[2].original, "\\ud800");
+        assert_eq!(repairs[2].replacement, "\u{fffd}");
+    }
+
+    #[test]
+    fn test_tokenizer_truncated_escape() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        let tokens = tokenizer.tokenize_with_repairs(r#"["ab\u12"#, &mut repairs);
+
+        assert_eq!(tokens[1].value.as_deref(), Some("ab"));
+        assert_eq!(repairs[0].kind, RepairKind::TruncatedEscape);
+        assert_eq!(repairs[0].original, "\\u12");
+        assert_eq!(repairs[1].kind, RepairKind::ClosedString);
+    }
+
+    #[test]
+    fn test_tokenizer_mixed_scripts() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        let tokens = tokenizer.tokenize_with_repairs(
+            r#"{"ключ": "значение 中文 🎉👍🏽", ключ2: 東京, "esc": "\u00fc\é"}"#,
+            &mut repairs,
+        );
+
+        let values: Vec<&str> = tokens.iter().filter_map(|t| t.value.as_deref()).collect();
+        assert_eq!(
+            values,
+            vec![
+                "{", "ключ", ":", "значение 中文 🎉👍🏽", ",", "ключ2", ":", "東京", ",", "esc", ":",
+                "üé", "}"
+            ]
+        );
+        // ключ2 and 東京 are quoted, `\é` is not a JSON escape
+        let kinds: Vec<RepairKind> = repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RepairKind::QuotedIdentifier,
+                RepairKind::QuotedIdentifier,
+                RepairKind::InvalidEscape,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_drops_stray_characters() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        let tokens = tokenizer.tokenize_with_repairs("[1 → 2]", &mut repairs);
+
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(repairs[0].kind, RepairKind::DroppedToken);
+        assert_eq!(repairs[0].original, "→");
+        assert_eq!(repairs[0].span, Span::new(3, 6, 1, 4));
+    }
+
+    #[test]
+    fn test_tokenizer_malformed_numbers() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        let input = "[01, .5, +5, 0x1F, 1_000, 1.2.3]";
+        let tokens = tokenizer.tokenize_with_repairs(input, &mut repairs);
+
+        let numbers: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Number)
+            .filter_map(|t| t.value.as_deref())
+            .collect();
+        assert_eq!(numbers, vec!["1", "0.5", "5", "31", "1000"]);
+        assert_eq!(repairs.len(), 5);
+        assert!(repairs.iter().all(|r| r.kind == RepairKind::RepairedNumber));
+        assert_eq!(tokens[11].kind, TokenKind::Unknown);
+    }
+
+    #[test]
+    fn test_tokenizer_literal_actions() {
+        let options = CorrectorOptions::new()
+            .partial_literals(RepairAction::Drop)
+            .identifiers(RepairAction::Substitute(serde_json::json!([1, "x"])));
+        let mut tokenizer = JSONTokenizer::with_options(options);
+        let mut repairs = Vec::new();
+        let tokens = tokenizer.tokenize_with_repairs("[tr, abc, true]", &mut repairs);
+
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::LBracket,
+                TokenKind::Dropped,
+                TokenKind::Comma,
+                TokenKind::LBracket,
+                TokenKind::Number,
+                TokenKind::Comma,
+                TokenKind::String,
+                TokenKind::RBracket,
+                TokenKind::Comma,
+                TokenKind::True,
+                TokenKind::RBracket,
+                TokenKind::Eof,
+            ]
+        );
+        assert_eq!(tokens[4].span, Span::new(5, 8, 1, 6));
+        assert_eq!(repairs[0].replacement, "");
+        assert_eq!(repairs[1].replacement, r#"[1,"x"]"#);
+
+        // A substitute counts against the token limit, and one that does not fit stops there
+        let options = CorrectorOptions::new()
+            .identifiers(RepairAction::Substitute(serde_json::json!([1, "x"])))
+            .max_tokens(6);
+        let mut tokenizer = JSONTokenizer::with_options(options);
+        let mut repairs = Vec::new();
+        let tokens = tokenizer.tokenize_with_repairs("[0, abc, true]", &mut repairs);
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [TokenKind::LBracket, TokenKind::Number, TokenKind::Comma, TokenKind::Eof]
+        );
+        assert_eq!(
+            repairs,
+            [Repair::new(RepairKind::TokenLimited, Span::new(4, 14, 1, 5), "", "")]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_quoted_strings() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        let tokens = tokenizer.tokenize_with_repairs(
+            r#"{'it\'s': `say "hi"\`
+now`, 'a\"b': 'open"#,
+            &mut repairs,
+        );
+
+        let values: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::String)
+            .filter_map(|t| t.value.as_deref())
+            .collect();
+        assert_eq!(values, vec!["it's", "say \"hi\"`\nnow", "a\"b", "open"]);
+
+        let kinds: Vec<RepairKind> = repairs.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RepairKind::RequotedString,
+                RepairKind::RequotedString,
+                RepairKind::RequotedString,
+                RepairKind::RequotedString,
+                RepairKind::ClosedString,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_python_and_js_literals() {
+        let mut tokenizer = JSONTokenizer::new();
+        let input = "[None, undefined, True, False, NaN, -Infinity]";
+        let kinds: Vec<TokenKind> = tokenizer
+            .tokenize(input)
+            .iter()
+            .map(|t| t.kind)
+            .filter(|&k| k != TokenKind::Comma)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::LBracket,
+                TokenKind::Null,
+                TokenKind::Null,
+                TokenKind::True,
+                TokenKind::False,
+                TokenKind::Null,
+                TokenKind::Null,
+                TokenKind::RBracket,
+                TokenKind::Eof,
+            ]
+        );
+
+        let options = CorrectorOptions::new().non_finite_numbers(NonFiniteNumbers::String);
+        let tokens = JSONTokenizer::with_options(options).tokenize("Infinity");
+        assert_eq!(tokens[0].kind, TokenKind::String);
+        assert_eq!(tokens[0].value.as_deref(), Some("Infinity"));
+    }
+
+    #[test]
+    fn test_tokenizer_comments() {
+        let mut tokenizer = JSONTokenizer::new();
+        let mut repairs = Vec::new();
+        let input = "{\n  // note here\n  \"a\": 1, # hash note\n  /* block\n note */ \"b\": \"x // y /* z */ # w\"\n} /* open";
+        let tokens = tokenizer.tokenize_with_repairs(input, &mut repairs);
+
+        let values: Vec<&str> = tokens.iter().filter_map(|t| t.value.as_deref()).collect();
+        assert_eq!(values, vec!["{", "a", ":", "1", ",", "b", ":", "x // y /* z */ # w", "}"]);
+
+        assert_eq!(repairs.len(), 4);
+        assert!(repairs.iter().all(|r| r.kind == RepairKind::RemovedComment));
+        assert_eq!(repairs[0].original, "// note here");
+        assert_eq!(repairs[1].original, "# hash note");
+        assert_eq!(repairs[2].original, "/* block\n note */");
+        assert_eq!(repairs[3].original, "/* open");
+        assert_eq!((repairs[2].span.line, repairs[2].span.column), (4, 3));
+    }
+
     #[test]
     fn test_tokenizer_scientific_notation() {
         let mut tokenizer = JSONTokenizer::new();